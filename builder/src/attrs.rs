@@ -0,0 +1,541 @@
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Attribute, Expr, Field, Ident, LitBool, LitStr, Meta, Path, Token, Type, Visibility};
+
+use crate::Pattern;
+
+#[derive(Default, Clone)]
+pub struct StructAttrs {
+    pub name: Option<Ident>,
+    pub vis: Option<Visibility>,
+    pub derive: Vec<Path>,
+    pub struct_attrs: Vec<Meta>,
+    pub pattern: Option<Pattern>,
+    pub typestate: Option<Ident>,
+    pub constant: Option<Ident>,
+    pub no_std: bool,
+    pub no_alloc: bool,
+    pub deserialize: Option<Ident>,
+    pub default: Option<Ident>,
+    pub setter: SetterOptions,
+    pub build_fn: BuildFnOptions,
+}
+
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub each: Option<Ident>,
+    pub each_item: Option<EachItemAttr>,
+    pub default: Option<DefaultValue>,
+    pub setter: SetterOptions,
+    pub try_setter: bool,
+    pub name: Option<Ident>,
+    pub merge: Merge,
+    pub env: Option<LitStr>,
+    pub sub_builder: Option<SubBuilderAttr>,
+    pub skip: Option<Ident>,
+    pub optional: Option<Ident>,
+    pub required: Option<Ident>,
+    pub serde: Vec<Attribute>,
+    pub cfg: Vec<Attribute>,
+    pub setter_attrs: Vec<Attribute>,
+}
+
+pub enum EachItemAttr {
+    Item(Box<Type>),
+    Entry(Box<Type>, Box<Type>),
+}
+
+pub struct SubBuilderAttr {
+    pub key: Ident,
+    pub builder: Option<Path>,
+}
+
+#[derive(Default, Clone)]
+pub struct SetterOptions {
+    pub into: Option<bool>,
+    pub strip_option: Option<bool>,
+    pub name: Option<Ident>,
+    pub prefix: Option<Ident>,
+}
+
+#[derive(Default, Clone)]
+pub struct BuildFnOptions {
+    pub name: Option<Ident>,
+    pub validate: Option<Path>,
+    pub error: Option<Type>,
+    pub skip: bool,
+}
+
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum Merge {
+    #[default]
+    Append,
+    Replace,
+}
+
+pub enum DefaultValue {
+    Trait,
+    Expr(Expr),
+}
+
+impl DefaultValue {
+    pub fn to_expr(&self) -> TokenStream2 {
+        match self {
+            DefaultValue::Trait => quote! { core::default::Default::default() },
+            DefaultValue::Expr(expr) => quote! { #expr },
+        }
+    }
+}
+
+impl SetterOptions {
+    pub fn or(&self, fallback: &SetterOptions) -> SetterOptions {
+        SetterOptions {
+            into: self.into.or(fallback.into),
+            strip_option: self.strip_option.or(fallback.strip_option),
+            name: self.name.clone(),
+            prefix: self.prefix.clone().or_else(|| fallback.prefix.clone()),
+        }
+    }
+
+    pub fn setter_name(&self, field: &Ident) -> Ident {
+        match (&self.name, &self.prefix) {
+            (Some(name), _) => name.clone(),
+            (None, Some(prefix)) => format_ident!("{}_{}", prefix, field.unraw()),
+            (None, None) => field.clone(),
+        }
+    }
+}
+
+impl StructAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = StructAttrs::default();
+        let mut parser = Parser::default();
+
+        parser.parse(attrs, |parser, meta| {
+            if meta.path.is_ident("name") {
+                parser.once(meta, "name")?;
+                let lit_str = meta.value()?.parse::<LitStr>()?;
+                parsed.name = Some(lit_str.parse().map_err(|_| {
+                    syn::Error::new(lit_str.span(), "expected `builder(name = \"...\")` with an identifier")
+                })?);
+            } else if meta.path.is_ident("vis") {
+                parser.once(meta, "vis")?;
+                let lit_str = meta.value()?.parse::<LitStr>()?;
+                parsed.vis = Some(lit_str.parse().map_err(|_| {
+                    syn::Error::new(lit_str.span(), "expected `builder(vis = \"...\")` with a visibility")
+                })?);
+            } else if meta.path.is_ident("derive") {
+                let content;
+                parenthesized!(content in meta.input);
+                parsed.derive.extend(Punctuated::<Path, Token![,]>::parse_terminated(&content)?);
+            } else if meta.path.is_ident("struct_attr") {
+                let content;
+                parenthesized!(content in meta.input);
+                parsed.struct_attrs.extend(Punctuated::<Meta, Token![,]>::parse_terminated(&content)?);
+            } else if meta.path.is_ident("pattern") {
+                parser.once(meta, "pattern")?;
+                let lit_str = meta.value()?.parse::<LitStr>()?;
+                parsed.pattern = Some(match lit_str.value().as_str() {
+                    "mutable" => Pattern::Mutable,
+                    "owned" => Pattern::Owned,
+                    "immutable" => Pattern::Immutable,
+                    _ => {
+                        return Err(syn::Error::new(
+                            lit_str.span(),
+                            "expected one of `\"mutable\"`, `\"owned\"` or `\"immutable\"`",
+                        ))
+                    }
+                });
+            } else if meta.path.is_ident("typestate") {
+                parser.once(meta, "typestate")?;
+                parsed.typestate = meta.path.get_ident().cloned();
+            } else if meta.path.is_ident("no_std") {
+                parser.once(meta, "no_std")?;
+                parsed.no_std = true;
+            } else if meta.path.is_ident("no_alloc") {
+                parser.once(meta, "no_alloc")?;
+                parsed.no_std = true;
+                parsed.no_alloc = true;
+            } else if meta.path.is_ident("const") {
+                parser.once(meta, "const")?;
+                parsed.constant = meta.path.get_ident().cloned();
+            } else if meta.path.is_ident("default") {
+                parser.once(meta, "default")?;
+                parsed.default = meta.path.get_ident().cloned();
+            } else if meta.path.is_ident("deserialize") {
+                parser.once(meta, "deserialize")?;
+                if !cfg!(feature = "serde") {
+                    return Err(meta.error("`deserialize` requires the `serde` feature of derive_builder"));
+                }
+                parsed.deserialize = meta.path.get_ident().cloned();
+            } else if meta.path.is_ident("setter") {
+                parser.setter_options(meta, &mut parsed.setter)?;
+            } else if meta.path.is_ident("build_fn") {
+                parser.build_fn_options(meta, &mut parsed.build_fn)?;
+            } else if let Some((ident, suggestion)) = meta.path.get_ident()
+                .and_then(|ident| Some((ident, suggest(ident, STRUCT_KEYS)?)))
+            {
+                return Err(meta.error(format!("unknown builder attribute `{}`, did you mean `{}`?", ident, suggestion)));
+            } else {
+                return Err(unknown_key(meta, STRUCT_KEYS));
+            }
+            Ok(())
+        });
+
+        if let Some(name) = &parsed.setter.name {
+            parser.push(syn::Error::new(name.span(), "`setter(name)` is only supported on fields"));
+        }
+
+        parser.finish(parsed)
+    }
+
+    pub fn or(self, fallback: &StructAttrs) -> StructAttrs {
+        StructAttrs {
+            name: self.name,
+            vis: self.vis.or_else(|| fallback.vis.clone()),
+            derive: [fallback.derive.as_slice(), self.derive.as_slice()].concat(),
+            struct_attrs: [fallback.struct_attrs.as_slice(), self.struct_attrs.as_slice()].concat(),
+            pattern: self.pattern.or(fallback.pattern),
+            typestate: self.typestate.or_else(|| fallback.typestate.clone()),
+            constant: self.constant.or_else(|| fallback.constant.clone()),
+            no_std: self.no_std || fallback.no_std,
+            no_alloc: self.no_alloc || fallback.no_alloc,
+            deserialize: self.deserialize.or_else(|| fallback.deserialize.clone()),
+            default: self.default.or_else(|| fallback.default.clone()),
+            setter: self.setter.or(&fallback.setter),
+            build_fn: BuildFnOptions {
+                name: self.build_fn.name.or_else(|| fallback.build_fn.name.clone()),
+                validate: self.build_fn.validate.or_else(|| fallback.build_fn.validate.clone()),
+                error: self.build_fn.error.or_else(|| fallback.build_fn.error.clone()),
+                skip: self.build_fn.skip || fallback.build_fn.skip,
+            },
+        }
+    }
+}
+
+impl FieldAttrs {
+    pub fn parse(field: &Field) -> syn::Result<Self> {
+        let mut parsed = FieldAttrs {
+            serde: field.attrs.iter().filter(|attr| attr.path().is_ident("serde")).cloned().collect(),
+            cfg: field.attrs.iter()
+                .filter(|attr| attr.path().is_ident("cfg") || attr.path().is_ident("cfg_attr"))
+                .cloned()
+                .collect(),
+            setter_attrs: field.attrs.iter()
+                .filter(|attr| attr.path().is_ident("doc") || attr.path().is_ident("deprecated"))
+                .cloned()
+                .collect(),
+            ..FieldAttrs::default()
+        };
+        let mut parser = Parser::default();
+        let mut merge = None;
+
+        parser.parse(&field.attrs, |parser, meta| {
+            if meta.path.is_ident("each") {
+                parser.once(meta, "each")?;
+                if meta.input.peek(Token![=]) {
+                    parsed.each = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                } else {
+                    let (each, each_item) = parse_each(meta)?;
+                    parsed.each = Some(each);
+                    parsed.each_item = each_item;
+                }
+            } else if meta.path.is_ident("default") {
+                parser.once(meta, "default")?;
+                parsed.default = Some(if meta.input.peek(Token![=]) {
+                    let lit_str = meta.value()?.parse::<LitStr>()?;
+                    let expr = lit_str.parse::<Expr>().map_err(|err| {
+                        syn::Error::new(lit_str.span(), format!("invalid default expression: {}", err))
+                    })?;
+                    DefaultValue::Expr(expr)
+                } else {
+                    DefaultValue::Trait
+                });
+            } else if meta.path.is_ident("into") {
+                parser.once(meta, "setter(into)")?;
+                parsed.setter.into = Some(true);
+            } else if meta.path.is_ident("setter") {
+                parser.setter_options(meta, &mut parsed.setter)?;
+            } else if meta.path.is_ident("skip") {
+                parser.once(meta, "skip")?;
+                parsed.skip = meta.path.get_ident().cloned();
+            } else if meta.path.is_ident("try_setter") {
+                parser.once(meta, "try_setter")?;
+                parsed.try_setter = true;
+            } else if meta.path.is_ident("name") {
+                parser.once(meta, "name")?;
+                if field.ident.is_some() {
+                    return Err(meta.error("`name` is only supported on tuple struct fields"));
+                }
+                let lit_str = meta.value()?.parse::<LitStr>()?;
+                parsed.name = Some(lit_str.parse().map_err(|_| {
+                    syn::Error::new(lit_str.span(), "expected `builder(name = \"...\")` with an identifier")
+                })?);
+            } else if meta.path.is_ident("merge") {
+                parser.once(meta, "merge")?;
+                let lit_str = meta.value()?.parse::<LitStr>()?;
+                parsed.merge = match lit_str.value().as_str() {
+                    "append" => Merge::Append,
+                    "replace" => Merge::Replace,
+                    _ => return Err(syn::Error::new(lit_str.span(), "expected `\"append\"` or `\"replace\"`")),
+                };
+                merge = Some(lit_str);
+            } else if meta.path.is_ident("env") {
+                parser.once(meta, "env")?;
+                parsed.env = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("sub_builder") {
+                parser.once(meta, "sub_builder")?;
+                let builder = if meta.input.peek(Token![=]) {
+                    Some(meta.value()?.parse::<LitStr>()?.parse()?)
+                } else {
+                    None
+                };
+                parsed.sub_builder = meta.path.get_ident().cloned().map(|key| SubBuilderAttr { key, builder });
+            } else if meta.path.is_ident("optional") || meta.path.is_ident("required") {
+                let key = meta.path.get_ident().cloned();
+                let name = key.as_ref().map(Ident::to_string).unwrap_or_default();
+                parser.once(meta, &name)?;
+                if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                    return Err(meta.error(format!("`{}` does not take a value", name)));
+                }
+                match name.as_str() {
+                    "optional" => parsed.optional = key,
+                    _ => parsed.required = key,
+                }
+            } else if let Some((ident, suggestion)) = meta.path.get_ident()
+                .and_then(|ident| Some((ident, suggest(ident, FIELD_KEYS)?)))
+            {
+                return Err(meta.error(format!("unknown builder attribute `{}`, did you mean `{}`?", ident, suggestion)));
+            } else {
+                return Err(unknown_key(meta, FIELD_KEYS));
+            }
+            Ok(())
+        });
+
+        if let Some(merge) = merge.filter(|_| parsed.each.is_none()) {
+            parser.push(syn::Error::new(merge.span(), "`merge` is only supported together with `each`"));
+        }
+        if let Some(env) = parsed.env.as_ref().filter(|_| parsed.each.is_some()) {
+            parser.push(syn::Error::new(env.span(), "`env` cannot be combined with `each`"));
+        }
+        if let Some(sub_builder) = &parsed.sub_builder {
+            if parsed.each.is_some() || parsed.default.is_some() || parsed.env.is_some() || parsed.try_setter {
+                parser.push(syn::Error::new(
+                    sub_builder.key.span(),
+                    "`sub_builder` cannot be combined with `each`, `default`, `env` or `try_setter`",
+                ));
+            }
+        }
+        if let (Some(optional), Some(_)) = (&parsed.optional, &parsed.required) {
+            parser.push(syn::Error::new(optional.span(), "`optional` and `required` cannot be combined"));
+        }
+        if let Some(key) = parsed.optional.as_ref().or(parsed.required.as_ref()) {
+            if parsed.each.is_some() || parsed.sub_builder.is_some() {
+                parser.push(syn::Error::new(
+                    key.span(),
+                    format!("`{}` cannot be combined with `each` or `sub_builder`", key),
+                ));
+            }
+        }
+        if let Some(skip) = &parsed.skip {
+            if parsed.each.is_some() || parsed.env.is_some() || parsed.sub_builder.is_some() || parsed.try_setter {
+                parser.push(syn::Error::new(
+                    skip.span(),
+                    "`skip` cannot be combined with `each`, `env`, `sub_builder` or `try_setter`",
+                ));
+            }
+        }
+
+        parser.finish(parsed)
+    }
+}
+
+#[derive(Default)]
+struct Parser {
+    seen: Vec<String>,
+    errors: Option<syn::Error>,
+}
+
+impl Parser {
+    fn parse(&mut self, attrs: &[Attribute], mut f: impl FnMut(&mut Parser, &ParseNestedMeta) -> syn::Result<()>) {
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("builder")) {
+            let result = attr.parse_nested_meta(|meta| {
+                if let Err(err) = f(self, &meta) {
+                    self.push(err);
+                    skip_value(&meta)?;
+                }
+                Ok(())
+            });
+            if let Err(err) = result {
+                self.push(err);
+            }
+        }
+    }
+
+    fn push(&mut self, err: syn::Error) {
+        match &mut self.errors {
+            Some(errors) => errors.combine(err),
+            None => self.errors = Some(err),
+        }
+    }
+
+    fn finish<T>(self, parsed: T) -> syn::Result<T> {
+        match self.errors {
+            Some(errors) => Err(errors),
+            None => Ok(parsed),
+        }
+    }
+
+    fn once(&mut self, meta: &ParseNestedMeta, key: &str) -> syn::Result<()> {
+        if self.seen.iter().any(|seen| seen == key) {
+            return Err(meta.error(format!("duplicate `{}` in builder attributes", key)));
+        }
+        self.seen.push(key.to_owned());
+        Ok(())
+    }
+
+    fn setter_options(&mut self, meta: &ParseNestedMeta, options: &mut SetterOptions) -> syn::Result<()> {
+        meta.parse_nested_meta(|nested| {
+            if nested.path.is_ident("into") {
+                self.once(&nested, "setter(into)")?;
+                options.into = Some(parse_flag(&nested)?);
+            } else if nested.path.is_ident("strip_option") {
+                self.once(&nested, "setter(strip_option)")?;
+                options.strip_option = Some(parse_flag(&nested)?);
+            } else if nested.path.is_ident("name") {
+                self.once(&nested, "setter(name)")?;
+                options.name = Some(parse_ident(&nested, "setter(name = \"...\")")?);
+            } else if nested.path.is_ident("prefix") {
+                self.once(&nested, "setter(prefix)")?;
+                options.prefix = Some(parse_ident(&nested, "setter(prefix = \"...\")")?);
+            } else {
+                return Err(nested.error("expected `into`, `strip_option`, `name` or `prefix`"));
+            }
+            Ok(())
+        })
+    }
+
+    fn build_fn_options(&mut self, meta: &ParseNestedMeta, options: &mut BuildFnOptions) -> syn::Result<()> {
+        meta.parse_nested_meta(|nested| {
+            if nested.path.is_ident("name") {
+                self.once(&nested, "build_fn(name)")?;
+                options.name = Some(nested.value()?.parse::<LitStr>()?.parse()?);
+            } else if nested.path.is_ident("validate") {
+                self.once(&nested, "build_fn(validate)")?;
+                options.validate = Some(nested.value()?.parse::<LitStr>()?.parse()?);
+            } else if nested.path.is_ident("error") {
+                self.once(&nested, "build_fn(error)")?;
+                options.error = Some(nested.value()?.parse::<LitStr>()?.parse()?);
+            } else if nested.path.is_ident("skip") {
+                self.once(&nested, "build_fn(skip)")?;
+                options.skip = parse_flag(&nested)?;
+            } else {
+                return Err(nested.error("expected `name`, `validate`, `error` or `skip`"));
+            }
+            Ok(())
+        })
+    }
+}
+
+fn parse_flag(meta: &ParseNestedMeta) -> syn::Result<bool> {
+    if meta.input.peek(Token![=]) {
+        Ok(meta.value()?.parse::<LitBool>()?.value)
+    } else {
+        Ok(true)
+    }
+}
+
+fn parse_each(meta: &ParseNestedMeta) -> syn::Result<(Ident, Option<EachItemAttr>)> {
+    let mut name = None;
+    let mut item = None;
+    let mut key = None;
+    let mut value = None;
+    meta.parse_nested_meta(|nested| {
+        let slot = if nested.path.is_ident("name") {
+            name = Some(parse_ident(&nested, "each(name = \"...\")")?);
+            return Ok(());
+        } else if nested.path.is_ident("item") {
+            &mut item
+        } else if nested.path.is_ident("key") {
+            &mut key
+        } else if nested.path.is_ident("value") {
+            &mut value
+        } else {
+            return Err(nested.error("expected `name`, `item`, `key` or `value`"));
+        };
+        *slot = Some(nested.value()?.parse::<LitStr>()?.parse::<Type>()?);
+        Ok(())
+    })?;
+
+    let Some(name) = name else {
+        return Err(meta.error("expected `each(name = \"...\")`"));
+    };
+    let each_item = match (item, key, value) {
+        (None, None, None) => None,
+        (Some(item), None, None) => Some(EachItemAttr::Item(Box::new(item))),
+        (None, Some(key), Some(value)) => Some(EachItemAttr::Entry(Box::new(key), Box::new(value))),
+        _ => return Err(meta.error("expected either `item = \"...\"` or both `key = \"...\"` and `value = \"...\"`")),
+    };
+    Ok((name, each_item))
+}
+
+fn parse_ident(meta: &ParseNestedMeta, expected: &str) -> syn::Result<Ident> {
+    let lit_str = meta.value()?.parse::<LitStr>()?;
+    lit_str.parse().map_err(|_| {
+        syn::Error::new(lit_str.span(), format!("expected `{}` with an identifier", expected))
+    })
+}
+
+const STRUCT_KEYS: &[&str] = &[
+    "name", "vis", "derive", "struct_attr", "pattern", "typestate", "no_std", "no_alloc", "const", "default",
+    "deserialize", "setter", "build_fn",
+];
+
+const FIELD_KEYS: &[&str] = &[
+    "each", "default", "into", "setter", "skip", "try_setter", "name", "merge", "env", "sub_builder", "optional",
+    "required",
+];
+
+fn unknown_key(meta: &ParseNestedMeta, keys: &[&str]) -> syn::Error {
+    let quoted = keys.iter().map(|key| format!("`{}`", key)).collect::<Vec<_>>();
+    let expected = match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        _ => quoted.concat(),
+    };
+    let path = meta.path.to_token_stream().to_string().replace(' ', "");
+    meta.error(format!("unknown builder attribute `{}`, expected {}", path, expected))
+}
+
+fn suggest<'a>(ident: &Ident, candidates: &[&'a str]) -> Option<&'a str> {
+    let ident = ident.to_string();
+    candidates.iter().copied()
+        .map(|candidate| (candidate, edit_distance(&ident, candidate)))
+        .filter(|&(candidate, distance)| (ident.len() >= 3 && candidate.starts_with(ident.as_str())) || distance <= 2)
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(candidate, _)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous + usize::from(a_char != *b_char);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    while !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+        meta.input.parse::<TokenTree>()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,375 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::visit_mut::{self, VisitMut};
+use syn::{Attribute, Field, FieldMutability, Fields, FnArg, GenericParam, Generics, ImplItem, Item, ItemFn, ItemImpl, Lifetime, LifetimeParam, Meta, ParenthesizedGenericArguments, Pat, ReturnType, Signature, Type, TypeBareFn, TypeImplTrait, TypeReference, Visibility, WhereClause, parse_quote};
+
+use crate::attrs::StructAttrs;
+use crate::{expand_builder, to_camel_case, BuilderTarget, TargetKind};
+
+pub fn expand(args: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    match syn::parse2::<Item>(item)? {
+        Item::Fn(item_fn) => {
+            let attrs = if args.is_empty() {
+                Vec::new()
+            } else {
+                vec![parse_quote! { #[builder(#args)] }]
+            };
+            expand_fn(item_fn, &attrs)
+        }
+        Item::Impl(item_impl) => {
+            if !args.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    args,
+                    "options go on the methods, e.g. `#[builder(name = \"...\")] fn new(...)`",
+                ));
+            }
+            expand_impl(item_impl)
+        }
+        item => Err(syn::Error::new_spanned(
+            item,
+            "`#[builder]` can only be used on functions and impl blocks",
+        )),
+    }
+}
+
+fn expand_fn(mut item_fn: ItemFn, attrs: &[Attribute]) -> syn::Result<TokenStream> {
+    let attrs = function_attrs(attrs, &item_fn.vis)?;
+    let signature = FnSignature::new(&mut item_fn.sig, None)?;
+    let fn_name = &item_fn.sig.ident;
+    let turbofish = &signature.turbofish;
+
+    let builder = expand_builder(BuilderTarget {
+        builder_name: attrs.name.clone().unwrap_or_else(|| {
+            format_ident!("{}Builder", to_camel_case(&fn_name.unraw().to_string()))
+        }),
+        builder_fn: format_ident!("{}_builder", fn_name.unraw()),
+        path: quote! { #fn_name #turbofish },
+        fields: &signature.fields,
+        attrs,
+        kind: signature.kind,
+        marker: signature.marker,
+        generics: signature.generics.clone(),
+        output: signature.output,
+        owner: None,
+        entry_generics: signature.generics,
+    })?;
+
+    Ok(quote! {
+        #item_fn
+
+        #builder
+    })
+}
+
+fn expand_impl(mut item_impl: ItemImpl) -> syn::Result<TokenStream> {
+    if let Some((_, path, _)) = &item_impl.trait_ {
+        return Err(syn::Error::new_spanned(path, "`#[builder]` cannot be used on trait impls"));
+    }
+
+    let self_ty = &*item_impl.self_ty;
+    let self_name = match self_ty {
+        Type::Path(type_path) => type_path.path.segments.last().map(|segment| segment.ident.unraw().to_string()),
+        _ => None,
+    };
+    let (impl_generics, _, where_clause) = item_impl.generics.split_for_impl();
+    let owner = quote! { impl #impl_generics #self_ty #where_clause };
+
+    let mut builders = Vec::new();
+    for impl_item in &mut item_impl.items {
+        let ImplItem::Fn(method) = impl_item else {
+            continue;
+        };
+        let (attrs, rest): (Vec<_>, Vec<_>) = method.attrs.drain(..).partition(|attr| attr.path().is_ident("builder"));
+        method.attrs = rest;
+        if attrs.is_empty() {
+            continue;
+        }
+        let attrs = attrs.into_iter().filter(|attr| matches!(attr.meta, Meta::List(_))).collect::<Vec<_>>();
+
+        let attrs = function_attrs(&attrs, &method.vis)?;
+        let signature = FnSignature::new(&mut method.sig, Some(self_ty))?;
+        let method_name = &method.sig.ident;
+        let turbofish = &signature.turbofish;
+
+        let (builder_name, builder_fn) = if method_name == "new" {
+            (self_name.as_ref().map(|name| format_ident!("{}Builder", name)), format_ident!("builder"))
+        } else {
+            let camel = to_camel_case(&method_name.unraw().to_string());
+            (
+                self_name.as_ref().map(|name| format_ident!("{}{}Builder", name, camel)),
+                format_ident!("{}_builder", method_name.unraw()),
+            )
+        };
+        let Some(builder_name) = attrs.name.clone().or(builder_name) else {
+            return Err(syn::Error::new_spanned(
+                self_ty,
+                "cannot derive a builder name from this type, use `#[builder(name = \"...\")]`",
+            ));
+        };
+
+        builders.push(expand_builder(BuilderTarget {
+            builder_name,
+            builder_fn,
+            path: quote! { <#self_ty>::#method_name #turbofish },
+            fields: &signature.fields,
+            attrs,
+            kind: signature.kind,
+            marker: signature.marker,
+            generics: merge_generics(&item_impl.generics, &signature.generics),
+            output: signature.output,
+            owner: Some(owner.clone()),
+            entry_generics: signature.generics,
+        })?);
+    }
+
+    Ok(quote! {
+        #item_impl
+
+        #(#builders)*
+    })
+}
+
+fn function_attrs(attrs: &[Attribute], vis: &Visibility) -> syn::Result<StructAttrs> {
+    let mut attrs = StructAttrs::parse(attrs)?;
+    if let Some(default) = &attrs.default {
+        return Err(syn::Error::new_spanned(default, "struct-level `default` is not supported on functions"));
+    }
+    if let Some(constant) = &attrs.constant {
+        return Err(syn::Error::new_spanned(constant, "`const` is not supported on functions"));
+    }
+    attrs.vis.get_or_insert_with(|| vis.clone());
+    attrs.build_fn.name.get_or_insert_with(|| format_ident!("call"));
+    Ok(attrs)
+}
+
+struct FnSignature {
+    fields: Fields,
+    generics: Generics,
+    output: TokenStream,
+    turbofish: Option<TokenStream>,
+    marker: Option<TokenStream>,
+    kind: TargetKind,
+}
+
+impl FnSignature {
+    fn new(sig: &mut Signature, self_ty: Option<&Type>) -> syn::Result<Self> {
+        let mut replace_self = ReplaceSelf { self_ty, error: None };
+        let mut elided = ElidedLifetimes::default();
+        let mut fields: syn::FieldsNamed = parse_quote! { {} };
+
+        for input in &mut sig.inputs {
+            let pat_type = match input {
+                FnArg::Typed(pat_type) => pat_type,
+                FnArg::Receiver(receiver) => {
+                    return Err(syn::Error::new_spanned(
+                        receiver,
+                        "`#[builder]` does not support methods that take `self`",
+                    ));
+                }
+            };
+            let Pat::Ident(pat_ident) = &*pat_type.pat else {
+                return Err(syn::Error::new_spanned(
+                    &pat_type.pat,
+                    "`#[builder]` parameters must be plain identifiers",
+                ));
+            };
+            if let Some(by_ref) = &pat_ident.by_ref {
+                return Err(syn::Error::new_spanned(by_ref, "`#[builder]` parameters must be plain identifiers"));
+            }
+
+            let (mut attrs, rest): (Vec<_>, Vec<_>) = pat_type.attrs.drain(..).partition(|attr| {
+                attr.path().is_ident("builder") || attr.path().is_ident("serde")
+            });
+            attrs.extend(rest.iter().filter(|attr| attr.path().is_ident("cfg") || attr.path().is_ident("cfg_attr")).cloned());
+            pat_type.attrs = rest;
+
+            let mut ty = (*pat_type.ty).clone();
+            reject_impl_trait(&mut ty)?;
+            replace_self.visit_type_mut(&mut ty);
+            elided.visit_type_mut(&mut ty);
+
+            fields.named.push(Field {
+                attrs,
+                vis: Visibility::Inherited,
+                mutability: FieldMutability::None,
+                ident: Some(pat_ident.ident.clone()),
+                colon_token: Some(Default::default()),
+                ty,
+            });
+        }
+
+        let mut output = match &sig.output {
+            ReturnType::Default => parse_quote! { () },
+            ReturnType::Type(_, ty) => (**ty).clone(),
+        };
+        replace_self.visit_type_mut(&mut output);
+        if let Some(error) = replace_self.error {
+            return Err(error);
+        }
+        let explicit_lifetimes = sig.generics.lifetimes().map(|param| param.lifetime.clone()).collect::<Vec<_>>();
+        let output_lifetime = match (elided.fresh.as_slice(), explicit_lifetimes.as_slice()) {
+            ([lifetime], []) | ([], [lifetime]) => Some(lifetime.clone()),
+            _ => None,
+        };
+        if let Some(lifetime) = output_lifetime {
+            OutputLifetime(lifetime).visit_type_mut(&mut output);
+        }
+
+        let mut generics = sig.generics.clone();
+        let fresh = elided.fresh.iter().map(|lifetime| GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())));
+        let position = generics.lifetimes().count();
+        for (offset, param) in fresh.enumerate() {
+            generics.params.insert(position + offset, param);
+        }
+
+        let turbofish_args = sig.generics.params.iter().filter_map(|param| match param {
+            GenericParam::Type(param) => Some(&param.ident),
+            GenericParam::Const(param) => Some(&param.ident),
+            GenericParam::Lifetime(_) => None,
+        }).collect::<Vec<_>>();
+        let turbofish = (!turbofish_args.is_empty()).then(|| quote! { ::<#(#turbofish_args),*> });
+
+        let marker_types = generics.params.iter().filter_map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                Some(quote! { &#lifetime () })
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                Some(quote! { #ident })
+            }
+            GenericParam::Const(_) => None,
+        }).collect::<Vec<_>>();
+        let marker = (!marker_types.is_empty()).then(|| quote! { fn() -> (#(#marker_types,)*) });
+
+        Ok(FnSignature {
+            fields: Fields::Named(fields),
+            generics,
+            output: quote! { #output },
+            turbofish,
+            marker,
+            kind: TargetKind::Function {
+                asyncness: sig.asyncness.is_some(),
+                unsafety: sig.unsafety.is_some(),
+            },
+        })
+    }
+}
+
+fn merge_generics(outer: &Generics, inner: &Generics) -> Generics {
+    let (lifetimes, params): (Vec<_>, Vec<_>) = outer.params.iter()
+        .chain(&inner.params)
+        .cloned()
+        .partition(|param| matches!(param, GenericParam::Lifetime(_)));
+    let predicates = outer.where_clause.iter()
+        .chain(&inner.where_clause)
+        .flat_map(|where_clause| where_clause.predicates.iter().cloned())
+        .collect::<Vec<_>>();
+
+    Generics {
+        lt_token: Some(Default::default()),
+        params: lifetimes.into_iter().chain(params).collect(),
+        gt_token: Some(Default::default()),
+        where_clause: (!predicates.is_empty()).then(|| WhereClause {
+            where_token: Default::default(),
+            predicates: predicates.into_iter().collect(),
+        }),
+    }
+}
+
+fn reject_impl_trait(ty: &mut Type) -> syn::Result<()> {
+    struct Finder(Option<syn::Error>);
+
+    impl VisitMut for Finder {
+        fn visit_type_impl_trait_mut(&mut self, ty: &mut TypeImplTrait) {
+            self.0.get_or_insert_with(|| {
+                syn::Error::new_spanned(ty, "`impl Trait` parameters are not supported, use a named generic instead")
+            });
+        }
+    }
+
+    let mut finder = Finder(None);
+    finder.visit_type_mut(ty);
+    finder.0.map_or(Ok(()), Err)
+}
+
+struct ReplaceSelf<'a> {
+    self_ty: Option<&'a Type>,
+    error: Option<syn::Error>,
+}
+
+impl VisitMut for ReplaceSelf<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(type_path) = ty {
+            if type_path.qself.is_none() && type_path.path.is_ident("Self") {
+                match self.self_ty {
+                    Some(self_ty) => *ty = self_ty.clone(),
+                    None => {
+                        self.error.get_or_insert_with(|| {
+                            syn::Error::new_spanned(
+                                &*ty,
+                                "`Self` is only available when `#[builder]` is also placed on the impl block",
+                            )
+                        });
+                    }
+                }
+                return;
+            }
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+}
+
+#[derive(Default)]
+struct ElidedLifetimes {
+    fresh: Vec<Lifetime>,
+}
+
+impl ElidedLifetimes {
+    fn next(&mut self) -> Lifetime {
+        let lifetime = Lifetime::new(&format!("'__f{}", self.fresh.len()), Span::call_site());
+        self.fresh.push(lifetime.clone());
+        lifetime
+    }
+}
+
+impl VisitMut for ElidedLifetimes {
+    fn visit_type_reference_mut(&mut self, reference: &mut TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(self.next());
+        }
+        visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.next();
+        }
+    }
+
+    fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut ParenthesizedGenericArguments) {}
+}
+
+struct OutputLifetime(Lifetime);
+
+impl VisitMut for OutputLifetime {
+    fn visit_type_reference_mut(&mut self, reference: &mut TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(self.0.clone());
+        }
+        visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.0.clone();
+        }
+    }
+
+    fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut ParenthesizedGenericArguments) {}
+}
@@ -1,191 +1,1841 @@
-use syn::spanned::Spanned;
+mod attrs;
+mod function;
+
 use proc_macro::TokenStream;
 use proc_macro2::Span;
-use quote::quote;
-use syn::{DeriveInput, Ident, Data, Type, PathArguments, GenericArgument, Attribute, MetaNameValue, Expr, Lit, parse_macro_input};
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::ext::IdentExt;
+use syn::{DeriveInput, Ident, Data, Fields, Generics, GenericParam, Index, Member, Path, PathSegment, Type, PathArguments, GenericArgument, parse_macro_input, parse_quote};
 
-#[proc_macro_derive(Builder, attributes(builder))]
+use crate::attrs::{DefaultValue, EachItemAttr, FieldAttrs, Merge, StructAttrs, SubBuilderAttr};
+
+#[cfg_attr(not(feature = "serde"), proc_macro_derive(Builder, attributes(builder)))]
+#[cfg_attr(feature = "serde", proc_macro_derive(Builder, attributes(builder, serde)))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_attribute]
+pub fn builder(args: TokenStream, input: TokenStream) -> TokenStream {
+    function::expand(args.into(), input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct BuilderTarget<'a> {
+    builder_name: Ident,
+    builder_fn: Ident,
+    path: proc_macro2::TokenStream,
+    fields: &'a Fields,
+    attrs: StructAttrs,
+    kind: TargetKind,
+    marker: Option<proc_macro2::TokenStream>,
+    generics: Generics,
+    output: proc_macro2::TokenStream,
+    owner: Option<proc_macro2::TokenStream>,
+    entry_generics: Generics,
+}
+
+enum TargetKind {
+    Struct,
+    Variant,
+    Function { asyncness: bool, unsafety: bool },
+}
+
+struct BuilderField<'a> {
+    ident: Ident,
+    member: Member,
+    ty: &'a Type,
+    option: Option<Type>,
+    attrs: FieldAttrs,
+    sub_builder: Option<SubBuilder>,
+    struct_default: bool,
+}
+
+struct SubBuilder {
+    builder: Path,
+    error: Path,
+    nest: Path,
+    edit: Path,
+    variant: Ident,
+}
+
+impl SubBuilder {
+    fn new(ident: &Ident, ty: &Type, attr: &SubBuilderAttr) -> syn::Result<Self> {
+        let builder = match (&attr.builder, ty) {
+            (Some(builder), _) => builder.clone(),
+            (None, Type::Path(type_path)) if type_path.qself.is_none() && option_inner(ty).is_none() => {
+                let mut builder = type_path.path.clone();
+                if let Some(segment) = builder.segments.last_mut() {
+                    segment.ident = format_ident!("{}Builder", segment.ident);
+                }
+                builder
+            }
+            (None, _) => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "`sub_builder` requires a struct type that derives Builder, or `sub_builder = \"...\"` naming its builder",
+                ));
+            }
+        };
+        let sibling = |suffix: &str| {
+            let mut path = builder.clone();
+            if let Some(segment) = path.segments.last_mut() {
+                segment.ident = format_ident!("{}{}", segment.ident, suffix);
+                segment.arguments = PathArguments::None;
+            }
+            path
+        };
+        Ok(SubBuilder {
+            error: sibling("Error"),
+            nest: sibling("Nest"),
+            edit: sibling("Edit"),
+            builder,
+            variant: error_variant(ident),
+        })
+    }
+}
+
+// Positional fields are named `_0`, `_1`, ..., which camel case to bare
+// numbers, so their error variants become `Field0`, `Field1`, ...
+fn error_variant(ident: &Ident) -> Ident {
+    let camel = to_camel_case(&ident.unraw().to_string());
+    if camel.starts_with(|ch: char| ch.is_ascii_digit()) {
+        format_ident!("Field{}", camel)
+    } else {
+        format_ident!("{}", camel)
+    }
+}
+
+impl BuilderField<'_> {
+    fn needs_value(&self) -> bool {
+        self.option.is_none()
+            && self.attrs.each.is_none()
+            && self.attrs.default.is_none()
+            && self.sub_builder.is_none()
+            && !self.struct_default
+    }
+
+    fn is_required(&self) -> bool {
+        self.needs_value() && self.attrs.env.is_none()
+    }
+
+    fn env_ident(&self) -> Option<Ident> {
+        self.attrs.env.as_ref().map(|_| format_ident!("__env_{}", self.ident.unraw()))
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = &input.ident;
-    let builder_name = Ident::new(&format!("{}Builder", name), Span::call_site());
+    let attrs = StructAttrs::parse(&input.attrs)?;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let owner = quote! { impl #impl_generics #name #ty_generics #where_clause };
+    let output = quote! { #name #ty_generics };
 
-    let Data::Struct(data_struct) = input.data else {
-        panic!("builder is not used for struct");
+    match &input.data {
+        Data::Struct(data_struct) => expand_builder(BuilderTarget {
+            builder_name: attrs.name.clone().unwrap_or_else(|| format_ident!("{}Builder", name)),
+            builder_fn: format_ident!("builder"),
+            path: quote! { #name },
+            fields: &data_struct.fields,
+            attrs,
+            kind: TargetKind::Struct,
+            // Skipped fields leave the builder, so a generic parameter only
+            // they use would otherwise be unused.
+            marker: (!input.generics.params.is_empty()).then(|| quote! { fn() -> #output }),
+            generics: input.generics.clone(),
+            output,
+            owner: Some(owner),
+            entry_generics: Generics::default(),
+        }),
+        Data::Enum(data_enum) => {
+            if let Some(builder_name) = &attrs.name {
+                return Err(syn::Error::new_spanned(
+                    builder_name,
+                    "`name` must be set on each variant of an enum",
+                ));
+            }
+            data_enum.variants.iter().map(|variant| {
+                let variant_name = &variant.ident;
+                let attrs = StructAttrs::parse(&variant.attrs)?.or(&attrs);
+                if let Some(default) = &attrs.default {
+                    return Err(syn::Error::new_spanned(default, "struct-level `default` is not supported on enums"));
+                }
+                expand_builder(BuilderTarget {
+                    builder_name: attrs.name.clone().unwrap_or_else(|| format_ident!("{}{}Builder", name, variant_name)),
+                    builder_fn: format_ident!("{}_builder", to_snake_case(&variant_name.unraw().to_string())),
+                    path: quote! { #name::#variant_name },
+                    fields: &variant.fields,
+                    attrs,
+                    kind: TargetKind::Variant,
+                    marker: Some(quote! { fn() -> #output }),
+                    generics: input.generics.clone(),
+                    output: output.clone(),
+                    owner: Some(owner.clone()),
+                    entry_generics: Generics::default(),
+                })
+            }).collect()
+        }
+        Data::Union(data_union) => Err(syn::Error::new_spanned(
+            data_union.union_token,
+            "Builder cannot be derived for unions",
+        )),
+    }
+}
+
+fn expand_builder(target: BuilderTarget) -> syn::Result<proc_macro2::TokenStream> {
+    let BuilderTarget { builder_name, builder_fn, path, attrs, generics, output, owner, .. } = &target;
+    let error_name = format_ident!("{}Error", builder_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut errors: Option<syn::Error> = None;
+    let mut fields = Vec::new();
+    for (index, field) in target.fields.iter().enumerate() {
+        let field = FieldAttrs::parse(field).and_then(|field_attrs| {
+            let ident = field.ident.clone()
+                .or_else(|| field_attrs.name.clone())
+                .unwrap_or_else(|| format_ident!("_{}", index));
+            let member = match &field.ident {
+                Some(field_ident) => Member::Named(field_ident.clone()),
+                None => Member::Unnamed(Index::from(index)),
+            };
+            let sub_builder = field_attrs.sub_builder.as_ref()
+                .map(|attr| SubBuilder::new(&ident, &field.ty, attr))
+                .transpose()?;
+            let ty = &field.ty;
+            let option = match (&field_attrs.required, &field_attrs.optional) {
+                (Some(_), _) => None,
+                (None, Some(_)) => Some(option_inner(ty).cloned().unwrap_or_else(|| {
+                    parse_quote! { <#ty as core::iter::IntoIterator>::Item }
+                })),
+                (None, None) => option_inner(ty).cloned(),
+            };
+            Ok(BuilderField {
+                ident,
+                member,
+                ty,
+                option,
+                attrs: field_attrs,
+                sub_builder,
+                struct_default: attrs.default.is_some(),
+            })
+        });
+        match field {
+            Ok(field) => fields.push(field),
+            Err(err) => match &mut errors {
+                Some(errors) => errors.combine(err),
+                None => errors = Some(err),
+            },
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let field_idents = fields.iter().map(|field| &field.ident).collect::<Vec<_>>();
+    let field_bindings = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let cfg = &field.attrs.cfg;
+        match (&field.attrs.skip, target.fields) {
+            (Some(_), Fields::Named(_)) => quote! { #(#cfg)* #ident: _ },
+            (Some(_), _) => quote! { _ },
+            (None, Fields::Named(_)) => quote! { #(#cfg)* #ident },
+            (None, _) => quote! { #ident },
+        }
+    });
+    let field_values = fields.iter().map(|BuilderField { ident, attrs, .. }| {
+        let cfg = &attrs.cfg;
+        quote! { #(#cfg)* #ident }
+    });
+    let (construct, destructure) = match target.fields {
+        _ if matches!(target.kind, TargetKind::Function { .. }) => {
+            let call = |args: Vec<&Ident>| {
+                let call = match target.kind {
+                    TargetKind::Function { asyncness: true, .. } => quote! { #path(#(#args),*).await },
+                    _ => quote! { #path(#(#args),*) },
+                };
+                match target.kind {
+                    TargetKind::Function { unsafety: true, .. } => quote! { unsafe { #call } },
+                    _ => call,
+                }
+            };
+            // Every combination of predicates gets its own call, so parameters
+            // with the same predicate share one bit of the mask.
+            let mut predicates = Vec::<proc_macro2::TokenStream>::new();
+            let mut conditional = Vec::new();
+            for (index, field) in fields.iter().enumerate() {
+                let field_predicates = cfg_predicates(&field.attrs.cfg);
+                if field_predicates.is_empty() {
+                    continue;
+                }
+                let predicate = quote! { all(#(#field_predicates),*) };
+                let bit = match predicates.iter().position(|known| known.to_string() == predicate.to_string()) {
+                    Some(bit) => bit,
+                    None if predicates.len() == MAX_CFG_PREDICATES => {
+                        let cfg = field.attrs.cfg.iter().find(|attr| attr.path().is_ident("cfg"));
+                        return Err(syn::Error::new_spanned(
+                            cfg,
+                            format!(
+                                "at most {} distinct `cfg` predicates are supported on the parameters of a #[builder] function",
+                                MAX_CFG_PREDICATES,
+                            ),
+                        ));
+                    }
+                    None => {
+                        predicates.push(predicate);
+                        predicates.len() - 1
+                    }
+                };
+                conditional.push((index, bit));
+            }
+            if conditional.is_empty() {
+                (call(field_idents.clone()), quote! { _ })
+            } else {
+                let calls = (0..1usize << predicates.len()).map(|mask| {
+                    let enabled = |index: usize| {
+                        conditional.iter().find(|(conditional, _)| *conditional == index)
+                            .is_none_or(|(_, bit)| mask & (1 << bit) != 0)
+                    };
+                    let args = fields.iter().enumerate()
+                        .filter(|(index, _)| enabled(*index))
+                        .map(|(_, field)| &field.ident)
+                        .collect();
+                    let conditions = predicates.iter().enumerate().map(|(bit, predicate)| {
+                        if mask & (1 << bit) != 0 {
+                            predicate.clone()
+                        } else {
+                            quote! { not(#predicate) }
+                        }
+                    });
+                    let call = call(args);
+                    quote! {
+                        #[cfg(all(#(#conditions),*))]
+                        let __output = #call;
+                    }
+                });
+                (quote! { { #(#calls)* __output } }, quote! { _ })
+            }
+        }
+        Fields::Named(_) => (
+            quote! { #path { #(#field_values),* } },
+            quote! { #path { #(#field_bindings),* } },
+        ),
+        Fields::Unnamed(_) => (
+            quote! { #path(#(#field_idents),*) },
+            quote! { #path(#(#field_bindings),*) },
+        ),
+        Fields::Unit => (quote! { #path }, quote! { #path }),
     };
+    let (skipped, fields): (Vec<_>, Vec<_>) = fields.into_iter().partition(|field| field.attrs.skip.is_some());
 
-    let fields = data_struct
-        .fields
-        .iter()
-        .filter(|field| field.ident.is_some());
+    if let Some(typestate) = &attrs.typestate {
+        if attrs.pattern.is_some_and(|pattern| !matches!(pattern, Pattern::Owned)) {
+            return Err(syn::Error::new_spanned(typestate, "`typestate` builders always use the owned pattern"));
+        }
+    }
+    let typestate = attrs.typestate.is_some().then(|| {
+        Typestate::new(builder_name, generics, attrs, &fields)
+    });
+    let pattern = match typestate {
+        Some(_) => Pattern::Owned,
+        None if attrs.constant.is_some() => Pattern::Owned,
+        // Calling a function consumes its arguments, so function builders move
+        // them out by default rather than cloning them.
+        None if matches!(target.kind, TargetKind::Function { .. }) => attrs.pattern.unwrap_or(Pattern::Owned),
+        None => attrs.pattern.unwrap_or(Pattern::Mutable),
+    };
+    if matches!(target.kind, TargetKind::Function { .. }) && typestate.is_some() {
+        let cfg = fields.iter().filter(|field| field.is_required()).find_map(|field| field.attrs.cfg.first());
+        if let Some(cfg) = cfg {
+            return Err(syn::Error::new_spanned(cfg, "`cfg` is not supported on required parameters of a `typestate` builder"));
+        }
+    }
+    if typestate.is_none() {
+        // Appending in `merge` iterates the other builder's collection, which an
+        // explicit item type does not promise; replacing only compares it.
+        let each_item = fields.iter()
+            .filter(|field| field.attrs.merge == Merge::Append)
+            .find_map(|field| field.attrs.each_item.as_ref());
+        if let Some(each_item) = each_item {
+            let ty = match each_item {
+                EachItemAttr::Item(item) => item,
+                EachItemAttr::Entry(key, _) => key,
+            };
+            return Err(syn::Error::new_spanned(
+                ty,
+                "`merge` appends by iterating the collection, which an explicit item type does not guarantee; add `merge = \"replace\"` to this field",
+            ));
+        }
+    }
+    if let Some(env) = fields.iter().find_map(|field| field.attrs.env.as_ref()).filter(|_| attrs.no_std) {
+        return Err(syn::Error::new_spanned(env, "`env` reads the process environment and cannot be combined with `no_std`"));
+    }
+    let alloc = match (attrs.no_std, attrs.no_alloc) {
+        (false, _) => Some(quote! { std }),
+        (true, false) => Some(quote! { alloc }),
+        (true, true) => None,
+    };
+    let message_ty = match &alloc {
+        Some(alloc) => quote! { #alloc::string::String },
+        None => quote! { &'static str },
+    };
+    if let Some(constant) = &attrs.constant {
+        check_const(constant, attrs, fields.iter().chain(&skipped))?;
+    }
 
-    let unexpected_attrs = fields.clone().find_map(|field|
-        get_unexpected_attributes(&field.attrs).map(|err| err.to_compile_error())
-    );
+    let mut builder_generics = generics.clone();
+    if let Some(typestate) = &typestate {
+        builder_generics.params.extend(typestate.params());
+    }
+    let (builder_impl_generics, builder_ty_generics, _) = builder_generics.split_for_impl();
 
-    let builder_init = fields.clone().filter_map(|field| {
-        let attrs = &field.attrs;
-        field.ident.as_ref().map(|ident| {
-            if get_value_of_each(attrs).is_some() {
-                quote! {
-                    #ident: std::vec::Vec::new()
+    let mut marker_types = Vec::new();
+    marker_types.extend(target.marker.clone());
+    if let Some(typestate) = &typestate {
+        marker_types.extend(typestate.marker_types());
+    }
+    if let Some(deserialize) = attrs.deserialize.as_ref().filter(|_| typestate.is_some()) {
+        return Err(syn::Error::new_spanned(deserialize, "`deserialize` cannot be combined with `typestate`"));
+    }
+    let serde = attrs.deserialize.is_some();
+    let serde_skip = serde.then(|| quote! { #[serde(skip)] });
+    let marker_field = (!marker_types.is_empty()).then(|| quote! {
+        #serde_skip
+        __marker: core::marker::PhantomData<(#(#marker_types,)*)>
+    });
+    let marker_init = marker_field.as_ref().map(|_| quote! {
+        __marker: core::marker::PhantomData
+    });
+
+    let builder_init = fields.iter().map(|BuilderField { ident, ty, attrs, sub_builder, .. }| {
+        let cfg = &attrs.cfg;
+        if sub_builder.is_some() {
+            quote! {
+                #(#cfg)*
+                #ident: <#ty>::builder()
+            }
+        } else if attrs.each.is_some() {
+            quote! {
+                #(#cfg)*
+                #ident: core::default::Default::default()
+            }
+        } else {
+            quote! {
+                #(#cfg)*
+                #ident: core::option::Option::None
+            }
+        }
+    }).collect::<Vec<_>>();
+
+    let builder_field = fields.iter().map(|BuilderField { ident, ty, option, attrs, sub_builder, .. }| {
+        let serde_attrs = serde.then_some(&attrs.serde).into_iter().flatten();
+        let serde_default = (serde && attrs.each.is_some()).then(|| quote! { #[serde(default)] });
+        let cfg = &attrs.cfg;
+        if let Some(SubBuilder { builder, .. }) = sub_builder {
+            let serde_default = serde.then(|| {
+                let default = quote! { <#ty>::builder }.to_string();
+                quote! { #[serde(default = #default)] }
+            });
+            quote! {
+                #(#cfg)*
+                #(#serde_attrs)*
+                #serde_default
+                #ident: #builder
+            }
+        } else if option.is_some() || attrs.each.is_some() {
+            quote! {
+                #(#cfg)*
+                #(#serde_attrs)*
+                #serde_default
+                #ident: #ty
+            }
+        } else {
+            quote! {
+                #(#cfg)*
+                #(#serde_attrs)*
+                #ident: core::option::Option<#ty>
+            }
+        }
+    });
+
+    let nestable = !matches!(target.kind, TargetKind::Function { .. });
+    check_method_names(attrs, &fields, typestate.is_some(), alloc.is_some(), nestable)?;
+
+    let setters = fields.iter().map(|field| {
+        let BuilderField { ident, ty, .. } = field;
+        let cfg = &field.attrs.cfg;
+        let setter_attrs = &field.attrs.setter_attrs;
+        let setter_options = field.attrs.setter.or(&attrs.setter);
+        let setter_name = setter_options.setter_name(ident);
+        if let Some(SubBuilder { builder, edit, .. }) = &field.sub_builder {
+            let ident_mut = format_ident!("{}_mut", ident.unraw());
+            let arg = SetterArg {
+                generics: Some(quote! {
+                    <__F: for<'__builder> core::ops::FnOnce(<#builder as #edit>::Arg<'__builder>) -> <#builder as #edit>::Ret>
+                }),
+                arg: quote! { f: __F },
+                value: quote! { f },
+                error: None,
+            };
+            let setter = pattern.setter(&setter_name, &arg, quote! { #edit::__edit(&mut builder.#ident, value); });
+            return quote! {
+                #(#cfg)*
+                pub fn #ident_mut(&mut self) -> &mut #builder {
+                    &mut self.#ident
+                }
+
+                #(#cfg)*
+                #(#setter_attrs)*
+                #setter
+            };
+        }
+
+        let into = setter_options.into.unwrap_or(false);
+        let strip_option = setter_options.strip_option.unwrap_or(true);
+        let try_setter = field.attrs.try_setter;
+
+        let setter_variants = |name: &Ident, param: &Ident, ty: &Type, assign: proc_macro2::TokenStream| {
+            let mut variants = vec![(name.clone(), SetterArg::new(param, ty, into), assign.clone())];
+            if try_setter {
+                variants.push((format_ident!("try_{}", name.unraw()), SetterArg::try_new(param, ty), assign));
+            }
+            variants
+        };
+
+        // const fns cannot run destructors, so a const setter forgets the
+        // value it replaces instead of dropping it.
+        let (store, store_some) = match attrs.constant {
+            Some(_) => (
+                quote! { core::mem::forget(core::mem::replace(&mut builder.#ident, value)); },
+                quote! { core::mem::forget(builder.#ident.replace(value)); },
+            ),
+            None => (
+                quote! { builder.#ident = value; },
+                quote! { builder.#ident = core::option::Option::Some(value); },
+            ),
+        };
+
+        let variants = if let Some(arg_ty) = field.option.as_ref().filter(|_| strip_option) {
+            setter_variants(&setter_name, ident, arg_ty, store_some)
+        } else if field.option.is_some() {
+            setter_variants(&setter_name, ident, ty, store)
+        } else if let Some(each) = &field.attrs.each {
+            let assign = quote! { core::iter::Extend::extend(&mut builder.#ident, core::iter::once(value)); };
+            let mut variants = match get_each_item(ty, field.attrs.each_item.as_ref()) {
+                EachItem::Value(item_ty) => setter_variants(each, each, &item_ty, assign),
+                EachItem::Entry(key_ty, value_ty) => {
+                    let mut variants = vec![(each.clone(), SetterArg::new_entry(&key_ty, &value_ty, into), assign.clone())];
+                    if try_setter {
+                        variants.push((format_ident!("try_{}", each.unraw()), SetterArg::try_new_entry(&key_ty, &value_ty), assign));
+                    }
+                    variants
+                }
+            };
+            if *each != setter_name {
+                variants.extend(setter_variants(&setter_name, ident, ty, quote! { builder.#ident = value; }));
+            }
+            variants
+        } else {
+            setter_variants(&setter_name, ident, ty, store_some)
+        };
+
+        let extend = field.attrs.each.as_ref().map(|_| {
+            let arg = SetterArg::new_iter(ident, &get_each_item(ty, field.attrs.each_item.as_ref()).to_type());
+            let assign = quote! { core::iter::Extend::extend(&mut builder.#ident, value); };
+            let setter = pattern.setter(&format_ident!("extend_{}", ident.unraw()), &arg, assign);
+            quote! {
+                #(#cfg)*
+                #(#setter_attrs)*
+                #setter
+            }
+        });
+
+        let setters = variants.into_iter().map(|(setter_name, arg, assign)| {
+            let setter = match typestate.as_ref().filter(|_| field.is_required()) {
+                Some(typestate) => typestate.setter(&setter_name, ident, &arg, assign),
+                None if attrs.constant.is_some() => pattern.const_setter(&setter_name, &arg, assign),
+                None => pattern.setter(&setter_name, &arg, assign),
+            };
+            quote! {
+                #(#cfg)*
+                #(#setter_attrs)*
+                #setter
+            }
+        });
+
+        quote! {
+            #(#setters)*
+            #extend
+        }
+    });
+
+    let accessors = fields.iter().map(|field| {
+        let BuilderField { ident, ty, attrs, sub_builder, .. } = field;
+        let getter_name = format_ident!("get_{}", ident.unraw());
+        let is_set_name = format_ident!("is_{}_set", ident.unraw());
+        let clear_name = format_ident!("clear_{}", ident.unraw());
+        let cfg = &attrs.cfg;
+
+        if let Some(SubBuilder { builder, .. }) = sub_builder {
+            let clear = pattern.clearer(&clear_name, quote! { builder.#ident = <#ty>::builder(); });
+            quote! {
+                #(#cfg)*
+                pub fn #getter_name(&self) -> &#builder {
+                    &self.#ident
+                }
+
+                #(#cfg)*
+                #clear
+            }
+        } else if attrs.each.is_some() {
+            let clear = pattern.clearer(&clear_name, quote! { builder.#ident = core::default::Default::default(); });
+            quote! {
+                #(#cfg)*
+                pub fn #getter_name(&self) -> &#ty {
+                    &self.#ident
                 }
+
+                #(#cfg)*
+                #clear
+            }
+        } else {
+            let value_ty = field.option.as_ref().unwrap_or(ty);
+            let clear = (typestate.is_none() || !field.is_required()).then(|| {
+                pattern.clearer(&clear_name, quote! { builder.#ident = core::option::Option::None; })
+            });
+            let clear = clear.map(|clear| quote! {
+                #(#cfg)*
+                #clear
+            });
+            quote! {
+                #(#cfg)*
+                pub fn #getter_name(&self) -> core::option::Option<&#value_ty> {
+                    self.#ident.as_ref()
+                }
+
+                #(#cfg)*
+                pub fn #is_set_name(&self) -> bool {
+                    self.#ident.is_some()
+                }
+
+                #clear
+            }
+        }
+    });
+
+    let unset_checks = fields.iter().filter(|field| field.needs_value()).map(|field| {
+        let ident = &field.ident;
+        let ident_str = ident.unraw().to_string();
+        let cfg = &field.attrs.cfg;
+        let env_check = field.attrs.env.as_ref().map(|var| quote! { && std::env::var_os(#var).is_none() });
+        quote! {
+            #(#cfg)*
+            if self.#ident.is_none() #env_check {
+                unset_fields.push(#ident_str);
+            }
+        }
+    }).collect::<Vec<_>>();
+    let unset_fields_mut = (!unset_checks.is_empty()).then(|| quote! { mut });
+    let unset_fields = alloc.as_ref().map(|alloc| quote! {
+        pub fn unset_fields(&self) -> #alloc::vec::Vec<&'static str> {
+            let #unset_fields_mut unset_fields = #alloc::vec::Vec::new();
+            #(#unset_checks)*
+            unset_fields
+        }
+    });
+
+    let merge = typestate.is_none().then(|| {
+        let merge_fields = fields.iter().map(|BuilderField { ident, ty, attrs, sub_builder, .. }| {
+            let cfg = &attrs.cfg;
+            let merge_field = match (&attrs.each, attrs.merge) {
+                _ if sub_builder.is_some() => quote! {
+                    self.#ident.__merge(other.#ident);
+                },
+                (Some(_), Merge::Append) => quote! {
+                    core::iter::Extend::extend(&mut self.#ident, other.#ident);
+                },
+                (Some(_), Merge::Replace) if attrs.each_item.is_some() => quote! {
+                    if other.#ident != <#ty as core::default::Default>::default() {
+                        self.#ident = other.#ident;
+                    }
+                },
+                (Some(_), Merge::Replace) => quote! {
+                    let mut items = core::iter::IntoIterator::into_iter(other.#ident).peekable();
+                    if items.peek().is_some() {
+                        self.#ident = core::default::Default::default();
+                        core::iter::Extend::extend(&mut self.#ident, items);
+                    }
+                },
+                (None, _) => quote! {
+                    if other.#ident.is_some() {
+                        self.#ident = other.#ident;
+                    }
+                },
+            };
+            quote! {
+                #(#cfg)*
+                #merge_field
+            }
+        });
+        let arg = SetterArg {
+            generics: None,
+            arg: quote! { other: Self },
+            value: quote! { other },
+            error: None,
+        };
+        let merge = pattern.setter(&format_ident!("merge"), &arg, quote! { builder.__merge(value); });
+        quote! {
+            #[doc(hidden)]
+            pub fn __merge(&mut self, other: Self) {
+                #(#merge_fields)*
+            }
+
+            #merge
+        }
+    });
+
+    let env_reads = fields.iter().filter_map(|field| {
+        let BuilderField { ident, ty, attrs, .. } = field;
+        let var = attrs.env.as_ref()?;
+        let env_ident = field.env_ident()?;
+        let ident_str = ident.unraw().to_string();
+        let cfg = &attrs.cfg;
+        let value_ty = field.option.as_ref().unwrap_or(ty);
+        Some(quote! {
+            #(#cfg)*
+            let #env_ident = if self.#ident.is_some() {
+                core::option::Option::None
             } else {
-                quote! {
-                    #ident: std::option::Option::None
+                match std::env::var(#var) {
+                    core::result::Result::Ok(value) => core::option::Option::Some(
+                        <#value_ty as core::str::FromStr>::from_str(&value)
+                            .map_err(|err| std::string::ToString::to_string(&err)),
+                    ),
+                    core::result::Result::Err(std::env::VarError::NotPresent) => core::option::Option::None,
+                    core::result::Result::Err(err) => core::option::Option::Some(
+                        core::result::Result::Err(std::string::ToString::to_string(&err)),
+                    ),
                 }
             }
+            .transpose()
+            .map_err(|message| #error_name::InvalidEnvVar { field: #ident_str, var: #var, message })?;
         })
     });
 
-    let builder_field = fields.clone().filter_map(|field| {
-        let ty = &field.ty;
-        field.ident.as_ref().map(|ident| {
-            if is_option(ty) || get_value_of_each(&field.attrs).is_some() {
+    let struct_default = attrs.default.is_some();
+    let field_guards = fields.iter().map(|field| {
+        let BuilderField { ident, member, ty, attrs, .. } = field;
+        let value = match &field.sub_builder {
+            Some(SubBuilder { builder, nest, variant, .. }) => {
+                let built = match pattern {
+                    Pattern::Owned => quote! { { self.#ident }.__build() },
+                    Pattern::Mutable => quote_spanned! {ty.span()=>
+                        <#builder as #nest<&mut #builder, #ty>>::__build_nested(&mut self.#ident)
+                    },
+                    Pattern::Immutable => quote_spanned! {ty.span()=>
+                        <#builder as #nest<&#builder, #ty>>::__build_nested(&self.#ident)
+                    },
+                };
+                quote! { #built.map_err(#error_name::#variant)? }
+            }
+            None => {
+                let value = pattern.take_field(ident);
+                match field.env_ident() {
+                    Some(env_ident) => quote! { #value.or(#env_ident) },
+                    None => value,
+                }
+            }
+        };
+        let guard = if field.sub_builder.is_some() && struct_default {
+            quote! {
+                __default.#member = #value;
+            }
+        } else if attrs.each.is_some() && struct_default {
+            // Items replace the default collection; an empty one leaves it.
+            quote! {
+                let #ident = #value;
+                if #ident != <#ty as core::default::Default>::default() {
+                    __default.#member = #ident;
+                }
+            }
+        } else if field.option.is_some() && struct_default {
+            quote! {
+                if let core::option::Option::Some(value) = #value {
+                    __default.#member = core::option::Option::Some(value);
+                }
+            }
+        } else if field.sub_builder.is_some() || field.option.is_some() || attrs.each.is_some() {
+            quote! {
+                let #ident = #value;
+            }
+        } else if let Some(default) = &attrs.default {
+            let default = default.to_expr();
+            if struct_default {
                 quote! {
-                    #ident: #ty
+                    __default.#member = #value.unwrap_or_else(|| #default);
                 }
             } else {
                 quote! {
-                    #ident: std::option::Option<#ty>
+                    let #ident = #value.unwrap_or_else(|| #default);
                 }
             }
-        })
+        } else if struct_default {
+            quote! {
+                if let core::option::Option::Some(value) = #value {
+                    __default.#member = value;
+                }
+            }
+        } else {
+            quote! {
+                let core::option::Option::Some(#ident) = #value else {
+                    core::unreachable!();
+                };
+            }
+        };
+        let cfg = &attrs.cfg;
+        quote! {
+            #(#cfg)*
+            #guard
+        }
     });
 
-    let setters = fields.clone().filter_map(|field| {
-        let ty = &field.ty;
-        let attrs = &field.attrs;
-        field.ident.as_ref().map(|ident| {
-            if is_option(ty) {
-                let arg_ty = get_type_in_generics(ty);
+    let skipped_guards = skipped.iter().filter_map(|BuilderField { ident, member, attrs, .. }| {
+        let cfg = &attrs.cfg;
+        match (struct_default, &attrs.default) {
+            (true, Some(default)) => {
+                let default = default.to_expr();
+                Some(quote! { #(#cfg)* __default.#member = #default; })
+            }
+            (true, None) => None,
+            (false, default) => {
+                let default = default.as_ref().unwrap_or(&DefaultValue::Trait).to_expr();
+                Some(quote! { #(#cfg)* let #ident = #default; })
+            }
+        }
+    }).collect::<Vec<_>>();
 
-                quote! {
-                    pub fn #ident(&mut self, #ident: #arg_ty) -> &mut Self {
-                        self.#ident = std::option::Option::Some(#ident);
-                        self
-                    }
+    let (default_init, built) = if struct_default {
+        let mutability = (!fields.is_empty() || !skipped_guards.is_empty()).then(|| quote! { mut });
+        (
+            Some(quote! { let #mutability __default: #output = core::default::Default::default(); }),
+            quote! { __default },
+        )
+    } else {
+        (None, construct)
+    };
+
+    let uninitialized_checks = fields.iter().filter(|field| {
+        field.needs_value() && (typestate.is_none() || field.attrs.env.is_some())
+    }).map(|field| {
+        let ident = &field.ident;
+        let ident_str = ident.unraw().to_string();
+        let cfg = &field.attrs.cfg;
+        let env_check = field.env_ident().map(|env_ident| quote! { && #env_ident.is_none() });
+        let report = match alloc {
+            Some(_) => quote! { uninitialized_fields.push(#ident_str); },
+            None => quote! {
+                return core::result::Result::Err(core::convert::From::from(#error_name::UninitializedField(#ident_str)));
+            },
+        };
+        quote! {
+            #(#cfg)*
+            if self.#ident.is_none() #env_check {
+                #report
+            }
+        }
+    });
+    let uninitialized = match &alloc {
+        Some(alloc) => quote! {
+            let mut uninitialized_fields = #alloc::vec::Vec::<&'static str>::new();
+            #(#uninitialized_checks)*
+            match uninitialized_fields.len() {
+                0 => {}
+                1 => return core::result::Result::Err(core::convert::From::from(#error_name::UninitializedField(uninitialized_fields[0]))),
+                _ => return core::result::Result::Err(core::convert::From::from(#error_name::UninitializedFields(uninitialized_fields))),
+            }
+        },
+        None => quote! {
+            #(#uninitialized_checks)*
+        },
+    };
+
+
+    let vis = attrs.vis.clone().unwrap_or_else(|| parse_quote! { pub });
+    let derives = &attrs.derive;
+    let derive = (!derives.is_empty()).then(|| quote! { #[derive(#(#derives),*)] });
+    let struct_attrs = &attrs.struct_attrs;
+    let serde_derive = serde.then(|| quote! { #[derive(::serde::Deserialize)] });
+    let builder_derive = pattern.builder_derive()
+        .filter(|_| !derives.iter().any(|path| path.segments.last().is_some_and(|segment| segment.ident == "Clone")));
+    let build_receiver = pattern.build_receiver();
+
+    let typestate_items = typestate.as_ref().map(|typestate| typestate.items());
+    let build_predicates = typestate.iter().flat_map(|typestate| typestate.build_bounds()).collect::<Vec<_>>();
+    let build_bounds = (!build_predicates.is_empty()).then(|| quote! { where #(#build_predicates),* });
+
+    // Enclosing builders reach a nested one through `&`, `&mut` or by value,
+    // following their own pattern. `<Builder>Nest<Receiver>` is implemented
+    // for the borrows this builder can be built through, so a mismatch is
+    // reported on the enclosing field rather than inside its build method.
+    let nest_name = format_ident!("{}Nest", builder_name);
+    let nest = nestable.then(|| {
+        let (message, note, borrows) = match pattern {
+            Pattern::Owned => (
+                "`{Self}` is built by value and can only be nested in a builder with the owned pattern",
+                "add `#[builder(pattern = \"owned\")]` to the enclosing struct",
+                Vec::new(),
+            ),
+            Pattern::Mutable => (
+                "`{Self}` is built through `&mut self` and cannot be nested in a builder with the immutable pattern",
+                "use the mutable or owned pattern on the enclosing struct",
+                vec![quote! { &'__builder mut }],
+            ),
+            Pattern::Immutable => (
+                "`{Self}` cannot be nested in this builder",
+                "`{Self}` uses the immutable pattern",
+                vec![quote! { &'__builder }, quote! { &'__builder mut }],
+            ),
+        };
+        let mut nest_generics = builder_generics.clone();
+        nest_generics.params.insert(0, parse_quote! { '__builder });
+        let (nest_impl_generics, _, _) = nest_generics.split_for_impl();
+        let mut nest_where = where_clause.cloned().unwrap_or_else(|| parse_quote! { where });
+        nest_where.predicates.extend(build_predicates.iter().map(|predicate| -> syn::WherePredicate { parse_quote! { #predicate } }));
+        quote! {
+            #[doc(hidden)]
+            #[diagnostic::on_unimplemented(message = #message, label = "required by this `sub_builder` field", note = #note)]
+            #vis trait #nest_name<Receiver, Output> {
+                fn __build_nested(receiver: Receiver) -> core::result::Result<Output, #error_name>;
+            }
+
+            #(impl #nest_impl_generics #nest_name<#borrows #builder_name #builder_ty_generics, #output> for #builder_name #builder_ty_generics #nest_where {
+                fn __build_nested(receiver: #borrows Self) -> core::result::Result<#output, #error_name> {
+                    receiver.__build()
                 }
+            })*
+        }
+    });
 
-            } else if let Some(each) = get_value_of_each(attrs) {
-                let each = Ident::new(&each, Span::call_site());
-                let arg_ty = get_type_in_generics(ty);
+    // The closure setter of an enclosing builder hands this builder out the
+    // way its own setters expect it: by value for the owned pattern, and
+    // through `&mut` otherwise.
+    let edit_name = format_ident!("{}Edit", builder_name);
+    let edit = nestable.then(|| {
+        let (arg, ret, body) = match pattern {
+            Pattern::Owned => (
+                quote! { Self },
+                quote! { Self },
                 quote! {
-                    pub fn #each(&mut self, #each: #arg_ty) -> &mut Self {
-                        self.#ident.push(#each);
-                        self
-                    }
+                    let builder = core::mem::replace(self, #builder_name {
+                        #(#builder_init,)*
+                        #marker_init
+                    });
+                    *self = f(builder);
+                },
+            ),
+            Pattern::Mutable | Pattern::Immutable => (quote! { &'__builder mut Self }, quote! { () }, quote! { f(self); }),
+        };
+        quote! {
+            #[doc(hidden)]
+            #vis trait #edit_name {
+                type Arg<'__builder> where Self: '__builder;
+                type Ret;
+
+                fn __edit<'__builder, __F: core::ops::FnOnce(Self::Arg<'__builder>) -> Self::Ret>(&'__builder mut self, f: __F);
+            }
+
+            impl #builder_impl_generics #edit_name for #builder_name #builder_ty_generics #where_clause {
+                type Arg<'__builder> = #arg where Self: '__builder;
+                type Ret = #ret;
+
+                fn __edit<'__builder, __F: core::ops::FnOnce(Self::Arg<'__builder>) -> Self::Ret>(&'__builder mut self, f: __F) {
+                    #body
                 }
+            }
+        }
+    });
+
+    let allow_deprecated = fields.iter().chain(&skipped)
+        .any(|field| field.attrs.setter_attrs.iter().any(|attr| attr.path().is_ident("deprecated")))
+        .then(|| quote! { #[allow(deprecated)] });
+
+    let to_builder = matches!(target.kind, TargetKind::Struct).then(|| {
+        let builder_ty = match &typestate {
+            Some(typestate) => typestate.filled_type(),
+            None => quote! { #builder_name #ty_generics },
+        };
+        let builder_values = fields.iter().map(|BuilderField { ident, option, attrs, sub_builder, .. }| {
+            let cfg = &attrs.cfg;
+            if sub_builder.is_some() {
+                quote! { #(#cfg)* #ident: core::convert::From::from(#ident) }
+            } else if option.is_some() || attrs.each.is_some() {
+                quote! { #(#cfg)* #ident: #ident }
             } else {
-                quote! {
-                    pub fn #ident(&mut self, #ident: #ty) -> &mut Self {
-                        self.#ident = std::option::Option::Some(#ident);
-                        self
+                quote! { #(#cfg)* #ident: core::option::Option::Some(#ident) }
+            }
+        });
+
+        quote! {
+            #allow_deprecated
+            impl #impl_generics core::convert::From<#output> for #builder_ty #where_clause {
+                fn from(value: #output) -> Self {
+                    let #destructure = value;
+                    #builder_name {
+                        #(#builder_values,)*
+                        #marker_init
                     }
                 }
             }
-        })
+
+            #owner {
+                #vis fn to_builder(&self) -> #builder_ty
+                where
+                    for<'__builder> #output: core::clone::Clone,
+                {
+                    core::convert::From::from(core::clone::Clone::clone(self))
+                }
+            }
+        }
     });
 
-    let field_guards = fields.clone().filter_map(|field| {
-        let ty = &field.ty;
-        let attrs = &field.attrs;
-        field.ident.as_ref().map(|ident| {
-            if is_option(ty) || get_value_of_each(attrs).is_some() {
-                quote! {
-                    let #ident = self.#ident.clone();
+    let (entry_impl_generics, _, entry_where_clause) = target.entry_generics.split_for_impl();
+    let constness = attrs.constant.as_ref().map(|_| quote! { const });
+    let entry_fn = quote! {
+        #vis #constness fn #builder_fn #entry_impl_generics() -> #builder_name #ty_generics #entry_where_clause {
+            #builder_name {
+                #(#builder_init,)*
+                #marker_init
+            }
+        }
+    };
+    let entry = match owner {
+        Some(owner) => quote! {
+            #owner {
+                #entry_fn
+            }
+        },
+        None => entry_fn,
+    };
+
+    let sub_builders = fields.iter().filter_map(|field| Some((field, field.sub_builder.as_ref()?))).collect::<Vec<_>>();
+    let sub_cfgs = sub_builders.iter().map(|(field, _)| {
+        let cfg = &field.attrs.cfg;
+        quote! { #(#cfg)* }
+    }).collect::<Vec<_>>();
+    let sub_variants = sub_builders.iter().map(|(_, sub_builder)| &sub_builder.variant).collect::<Vec<_>>();
+    let sub_errors = sub_builders.iter().map(|(_, sub_builder)| &sub_builder.error);
+    let sub_fields = sub_builders.iter().map(|(field, _)| field.ident.unraw().to_string());
+    let error_source = (!sub_builders.is_empty()).then(|| quote! {
+        fn source(&self) -> core::option::Option<&(dyn core::error::Error + 'static)> {
+            match self {
+                #(#sub_cfgs #error_name::#sub_variants(error) => core::option::Option::Some(error),)*
+                _ => core::option::Option::None,
+            }
+        }
+    });
+
+    let uninitialized_fields_variant = alloc.as_ref().map(|alloc| quote! {
+        UninitializedFields(#alloc::vec::Vec<&'static str>),
+    });
+    let uninitialized_fields_fmt = alloc.as_ref().map(|_| quote! {
+        #error_name::UninitializedFields(fields) => {
+            for (index, field) in fields.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(", ")?;
                 }
-            } else {
+                write_field(f, field)?;
+            }
+            f.write_str(" must be initialized")
+        }
+    });
+    let invalid_env_var_variant = (!attrs.no_std).then(|| quote! {
+        InvalidEnvVar {
+            field: &'static str,
+            var: &'static str,
+            message: std::string::String,
+        },
+    });
+    let invalid_env_var_fmt = (!attrs.no_std).then(|| quote! {
+        #error_name::InvalidEnvVar { field, var, message } => {
+            write_field(f, field)?;
+            core::write!(f, " could not be read from environment variable `{}`: {}", var, message)
+        }
+    });
+
+    let build_fn_options = &attrs.build_fn;
+    let build_fn = (!build_fn_options.skip || nestable).then(|| {
+        let build_fn_name = build_fn_options.name.clone().unwrap_or_else(|| format_ident!("build"));
+        let build_error = build_fn_options.error.as_ref().map_or_else(|| quote! { #error_name }, |error| quote! { #error });
+        let validate = build_fn_options.validate.as_ref().map(|validate| quote! {
+            if let core::result::Result::Err(message) = #validate(&self) {
+                return core::result::Result::Err(core::convert::From::from(#error_name::ValidationError(message)));
+            }
+        });
+
+        let (asyncness, unsafety) = match target.kind {
+            TargetKind::Function { asyncness, unsafety } => (
+                asyncness.then(|| quote! { async }),
+                unsafety.then(|| quote! { unsafe }),
+            ),
+            TargetKind::Struct | TargetKind::Variant => (None, None),
+        };
+
+        if attrs.constant.is_some() {
+            if build_fn_options.skip {
+                return quote! {};
+            }
+            // A const fn cannot drop `self`, even after moving every slot out
+            // of it, so the slots are read out of a `ManuallyDrop` exactly once
+            // each and then moved into the output or forgotten.
+            let const_guards = fields.iter().map(|BuilderField { ident, option, attrs, .. }| {
+                let cfg = &attrs.cfg;
+                let guard = if option.is_some() {
+                    None
+                } else if let Some(default) = &attrs.default {
+                    let default = default.to_expr();
+                    Some(quote! {
+                        let #ident = if #ident.is_some() {
+                            #ident.unwrap()
+                        } else {
+                            core::mem::forget(#ident);
+                            #default
+                        };
+                    })
+                } else {
+                    let message = format!("`{}` must be initialized", ident.unraw());
+                    Some(quote! {
+                        let #ident = #ident.expect(#message);
+                    })
+                };
                 quote! {
-                    let std::option::Option::Some(#ident) = self.#ident.clone() else {
-                        return std::result::Result::Err("field is not enough".to_string().into());
-                    };
+                    #(#cfg)*
+                    let #ident = unsafe { core::ptr::read(&__this.#ident) };
+                    #(#cfg)*
+                    #guard
+                }
+            });
+            return quote! {
+                #allow_deprecated
+                pub const fn #build_fn_name(self) -> #output {
+                    let __this = core::mem::ManuallyDrop::new(self);
+                    let __this = unsafe { &*(&__this as *const core::mem::ManuallyDrop<Self> as *const Self) };
+                    #(#const_guards)*
+                    #(#skipped_guards)*
+
+                    #built
+                }
+
+                #[doc(hidden)]
+                pub fn __build(self) -> core::result::Result<#output, #error_name> {
+                    core::result::Result::Ok(self.#build_fn_name())
                 }
+            };
+        }
+
+        let body = quote! {
+            #validate
+
+            #(#env_reads)*
+
+            #uninitialized
+
+            #default_init
+            #(#field_guards)*
+            #(#skipped_guards)*
+
+            core::result::Result::Ok(#built)
+        };
+        if !nestable {
+            return quote! {
+                #allow_deprecated
+                pub #asyncness #unsafety fn #build_fn_name(#build_receiver) -> core::result::Result<#output, #build_error> #build_bounds {
+                    #body
+                }
+            };
+        }
+
+        // Enclosing builders go through `__build`, which keeps the same name
+        // and error type whatever `build_fn` says.
+        let build_error_conversion = build_fn_options.error.as_ref().map(|_| quote! { .map_err(core::convert::From::from) });
+        let build_fn = (!build_fn_options.skip).then(|| quote! {
+            pub fn #build_fn_name(#build_receiver) -> core::result::Result<#output, #build_error> #build_bounds {
+                self.__build()#build_error_conversion
             }
-        })
+        });
+        quote! {
+            #[doc(hidden)]
+            #allow_deprecated
+            pub fn __build(#build_receiver) -> core::result::Result<#output, #error_name> #build_bounds {
+                #body
+            }
+
+            #build_fn
+        }
     });
 
-    let field_idents = fields.clone().filter_map(|field| field.ident.as_ref());
+    Ok(quote! {
+        #typestate_items
 
-    quote! {
-        #unexpected_attrs
+        #builder_derive
+        #derive
+        #serde_derive
+        #(#[#struct_attrs])*
+        #vis struct #builder_name #builder_generics #where_clause {
+            #(#builder_field,)*
+            #marker_field
+        }
 
-        pub struct #builder_name {
-            #(#builder_field),*
+        #[derive(core::fmt::Debug, core::clone::Clone, core::cmp::PartialEq, core::cmp::Eq)]
+        #vis enum #error_name {
+            UninitializedField(&'static str),
+            #uninitialized_fields_variant
+            ValidationError(#message_ty),
+            #invalid_env_var_variant
+            #(#sub_cfgs #sub_variants(#sub_errors),)*
         }
 
-        impl #name {
-            pub fn builder() -> #builder_name {
-                #builder_name {
-                    #(#builder_init),*
+        impl #error_name {
+            #[doc(hidden)]
+            pub fn __fmt_at(
+                &self,
+                path: core::option::Option<&dyn core::fmt::Display>,
+                f: &mut core::fmt::Formatter<'_>,
+            ) -> core::fmt::Result {
+                let write_field = |f: &mut core::fmt::Formatter<'_>, field: &str| match path {
+                    core::option::Option::Some(path) => core::write!(f, "`{}.{}`", path, field),
+                    core::option::Option::None => core::write!(f, "`{}`", field),
+                };
+                match self {
+                    #error_name::UninitializedField(field) => {
+                        write_field(f, field)?;
+                        f.write_str(" must be initialized")
+                    }
+                    #uninitialized_fields_fmt
+                    #error_name::ValidationError(message) => match path {
+                        core::option::Option::Some(path) => core::write!(f, "`{}`: {}", path, message),
+                        core::option::Option::None => f.write_str(message),
+                    },
+                    #invalid_env_var_fmt
+                    #(#sub_cfgs #error_name::#sub_variants(error) => match path {
+                        core::option::Option::Some(path) => {
+                            error.__fmt_at(core::option::Option::Some(&core::format_args!("{}.{}", path, #sub_fields)), f)
+                        }
+                        core::option::Option::None => error.__fmt_at(core::option::Option::Some(&#sub_fields), f),
+                    },)*
                 }
             }
         }
 
-        impl #builder_name {
+        impl core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                self.__fmt_at(core::option::Option::None, f)
+            }
+        }
+
+        impl core::error::Error for #error_name {
+            #error_source
+        }
+
+        #entry
+
+        #nest
+
+        #edit
+
+        impl #builder_impl_generics #builder_name #builder_ty_generics #where_clause {
             #(#setters)*
 
-            pub fn build(&mut self) -> std::result::Result<#name, std::boxed::Box<dyn std::error::Error>> {
-                #(#field_guards)*
+            #(#accessors)*
 
-                std::result::Result::Ok(#name {
-                    #(#field_idents),*
-                })
+            #unset_fields
+
+            #merge
+
+            #build_fn
+        }
+
+        #to_builder
+    })
+}
+
+fn check_method_names(attrs: &StructAttrs, fields: &[BuilderField], typestate: bool, alloc: bool, nestable: bool) -> syn::Result<()> {
+    let mut methods: Vec<(String, Option<&Ident>, String)> = Vec::new();
+    for field in fields {
+        let ident = &field.ident;
+        let field_name = ident.unraw();
+        let setter_name = field.attrs.setter.or(&attrs.setter).setter_name(ident);
+        let mut method = |name: String, what: String| methods.push((name, Some(ident), what));
+
+        if field.sub_builder.is_some() {
+            method(format!("{}_mut", field_name), format!("`_mut` accessor for `{}`", field_name));
+        }
+        let mut setters = Vec::new();
+        match &field.attrs.each {
+            Some(each) => {
+                setters.push(each.unraw().to_string());
+                if *each != setter_name {
+                    setters.push(setter_name.unraw().to_string());
+                }
+                method(format!("extend_{}", field_name), format!("`extend_` setter for `{}`", field_name));
             }
+            None => setters.push(setter_name.unraw().to_string()),
         }
-    }.into()
+        for setter in setters {
+            if field.attrs.try_setter {
+                method(format!("try_{}", setter), format!("`try_` setter for `{}`", field_name));
+            }
+            method(setter, format!("setter for `{}`", field_name));
+        }
+
+        method(format!("get_{}", field_name), format!("`get_` accessor for `{}`", field_name));
+        if field.sub_builder.is_none() && field.attrs.each.is_none() {
+            method(format!("is_{}_set", field_name), format!("`is_set` accessor for `{}`", field_name));
+        }
+        if !typestate || !field.is_required() {
+            method(format!("clear_{}", field_name), format!("`clear_` accessor for `{}`", field_name));
+        }
+    }
+    if alloc {
+        methods.push(("unset_fields".to_owned(), None, "`unset_fields` method".to_owned()));
+    }
+    if !typestate {
+        methods.push(("merge".to_owned(), None, "`merge` method".to_owned()));
+        methods.push(("__merge".to_owned(), None, "hidden `__merge` method".to_owned()));
+    }
+    if nestable && (attrs.constant.is_none() || !attrs.build_fn.skip) {
+        methods.push(("__build".to_owned(), None, "hidden `__build` method".to_owned()));
+    }
+    if !attrs.build_fn.skip {
+        let name = attrs.build_fn.name.as_ref();
+        let build = name.map_or_else(|| "build".to_owned(), |name| name.unraw().to_string());
+        methods.push((build, name, "build method".to_owned()));
+    }
+
+    let mut errors: Option<syn::Error> = None;
+    for (index, (name, ident, what)) in methods.iter().enumerate() {
+        let Some((_, other_ident, other_what)) = methods[..index].iter().find(|(other, ..)| other == name) else {
+            continue;
+        };
+        let span = ident.or(*other_ident).map_or_else(Span::call_site, Ident::span);
+        let err = syn::Error::new(
+            span,
+            format!(
+                "the {} and the {} are both named `{}`; rename one with `setter(name = \"...\")`",
+                other_what, what, name,
+            ),
+        );
+        match &mut errors {
+            Some(errors) => errors.combine(err),
+            None => errors = Some(err),
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
 }
 
-fn is_option(ty: &Type) -> bool {
-    let Type::Path(type_path) = ty else {
-        return false;
-    };
+/// Caps the number of calls a function builder emits, one per combination of
+/// distinct `cfg` predicates on its parameters.
+const MAX_CFG_PREDICATES: usize = 8;
 
-    type_path
-        .path
-        .segments
-        .first()
-        .map(|segment| segment.ident == "Option")
-        .unwrap_or(false)
+fn cfg_predicates(attrs: &[syn::Attribute]) -> Vec<proc_macro2::TokenStream> {
+    attrs.iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .filter_map(|attr| attr.parse_args().ok())
+        .collect()
 }
 
-fn get_type_in_generics(ty: &Type) -> Option<&Type> {
+fn std_type_segment(ty: &Type) -> Option<&PathSegment> {
     let Type::Path(type_path) = ty else {
         return None;
     };
-    let PathArguments::AngleBracketed(ref args) = type_path.path.segments.first()?.arguments else {
+    if type_path.qself.is_some() {
         return None;
+    }
+    let segments = &type_path.path.segments;
+    let is_std = match segments.first() {
+        Some(root) if segments.len() > 1 => {
+            ["std", "core", "alloc"].iter().any(|name| root.ident == name)
+                && segments.iter().rev().skip(1).all(|segment| segment.arguments.is_none())
+        }
+        _ => type_path.path.leading_colon.is_none(),
     };
-    let Some(GenericArgument::Type(ty)) = args.args.first() else {
-        return None;
+    segments.last().filter(|_| is_std)
+}
+
+fn generic_types(segment: &PathSegment) -> Vec<&Type> {
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return Vec::new();
     };
-    Some(ty)
+    args.args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }).collect()
 }
 
-fn get_value_of_each(attrs: &[Attribute]) -> Option<String> {
-    attrs
-        .first()
-        .and_then(|attr| attr.parse_args::<MetaNameValue>().ok())
-        .filter(|name_value| name_value.path.is_ident("each"))
-        .and_then(|name_value| {
-            let Expr::Lit(lit) = name_value.value else {
-                return None;
-            };
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let segment = std_type_segment(ty).filter(|segment| segment.ident == "Option")?;
+    match generic_types(segment).as_slice() {
+        [inner] => Some(inner),
+        _ => None,
+    }
+}
 
-            let Lit::Str(lit_str) = lit.lit else {
-                return None;
-            };
+#[derive(Clone, Copy)]
+enum Pattern {
+    Mutable,
+    Owned,
+    Immutable,
+}
+
+fn check_const<'a>(
+    constant: &Ident,
+    attrs: &StructAttrs,
+    fields: impl Iterator<Item = &'a BuilderField<'a>>,
+) -> syn::Result<()> {
+    let mut errors = Vec::new();
+    if attrs.typestate.is_some() {
+        errors.push(syn::Error::new_spanned(constant, "`const` cannot be combined with `typestate`"));
+    }
+    if attrs.pattern.is_some_and(|pattern| !matches!(pattern, Pattern::Owned)) {
+        errors.push(syn::Error::new_spanned(constant, "`const` builders always use the owned pattern"));
+    }
+    if let Some(default) = &attrs.default {
+        errors.push(syn::Error::new_spanned(default, "struct-level `default` is not supported by `const` builders"));
+    }
+    if attrs.build_fn.validate.is_some() || attrs.build_fn.error.is_some() {
+        errors.push(syn::Error::new_spanned(
+            constant,
+            "`const` builders panic on missing fields and do not support `build_fn(validate)` or `build_fn(error)`",
+        ));
+    }
+
+    for field in fields {
+        let FieldAttrs { each, default, env, sub_builder, try_setter, .. } = &field.attrs;
+        if let Some(each) = each {
+            errors.push(syn::Error::new_spanned(each, "`each` is not supported by `const` builders"));
+        }
+        if let Some(env) = env {
+            errors.push(syn::Error::new_spanned(env, "`env` is not supported by `const` builders"));
+        }
+        if let Some(sub_builder) = sub_builder {
+            errors.push(syn::Error::new_spanned(&sub_builder.key, "`sub_builder` is not supported by `const` builders"));
+        }
+        if *try_setter {
+            errors.push(syn::Error::new_spanned(&field.ident, "`try_setter` is not supported by `const` builders"));
+        }
+        if field.attrs.setter.or(&attrs.setter).into == Some(true) {
+            errors.push(syn::Error::new_spanned(&field.ident, "`setter(into)` is not supported by `const` builders"));
+        }
+        let needs_default = field.attrs.skip.is_some() && default.is_none();
+        if needs_default || matches!(default, Some(DefaultValue::Trait)) {
+            errors.push(syn::Error::new_spanned(
+                &field.ident,
+                "`const` builders need `default = \"...\"` with a const expression instead of `Default::default()`",
+            ));
+        }
+    }
+
+    match errors.into_iter().reduce(|mut errors, err| {
+        errors.combine(err);
+        errors
+    }) {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+impl Pattern {
+    fn receiver(&self) -> (proc_macro2::TokenStream, proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        match self {
+            Pattern::Mutable => (quote! { &mut self }, quote! { &mut Self }, quote! { let builder = self; }),
+            Pattern::Owned => (quote! { self }, quote! { Self }, quote! { let mut builder = self; }),
+            Pattern::Immutable => (
+                quote! { &self },
+                quote! { Self },
+                quote! { let mut builder = core::clone::Clone::clone(self); },
+            ),
+        }
+    }
+
+    fn setter(&self, name: &Ident, arg: &SetterArg, assign: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        self.setter_fn(None, name, arg, assign)
+    }
+
+    fn const_setter(&self, name: &Ident, arg: &SetterArg, assign: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let setter = self.setter_fn(Some(quote! { const }), name, arg, assign);
+        quote! {
+            ///
+            /// If the field is already set, its previous value is leaked rather
+            /// than dropped, because a `const fn` cannot run destructors.
+            #setter
+        }
+    }
+
+    fn setter_fn(
+        &self,
+        constness: Option<proc_macro2::TokenStream>,
+        name: &Ident,
+        arg: &SetterArg,
+        assign: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let (receiver, ty, binding) = self.receiver();
+        let SetterArg { generics, arg: param, value, .. } = arg;
+        let (ret, tail) = arg.wrap_return(ty, quote! { builder });
+
+        quote! {
+            pub #constness fn #name #generics(#receiver, #param) -> #ret {
+                let value = #value;
+                #binding
+                #assign
+                #tail
+            }
+        }
+    }
+
+    fn clearer(&self, name: &Ident, assign: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let (receiver, ty, binding) = self.receiver();
+
+        quote! {
+            pub fn #name(#receiver) -> #ty {
+                #binding
+                #assign
+                builder
+            }
+        }
+    }
+
+    fn build_receiver(&self) -> proc_macro2::TokenStream {
+        match self {
+            Pattern::Mutable => quote! { &mut self },
+            Pattern::Owned => quote! { self },
+            Pattern::Immutable => quote! { &self },
+        }
+    }
+
+    fn take_field(&self, ident: &Ident) -> proc_macro2::TokenStream {
+        match self {
+            Pattern::Owned => quote! { self.#ident },
+            Pattern::Mutable | Pattern::Immutable => quote! { core::clone::Clone::clone(&self.#ident) },
+        }
+    }
+
+    fn builder_derive(&self) -> Option<proc_macro2::TokenStream> {
+        match self {
+            Pattern::Immutable => Some(quote! { #[derive(core::clone::Clone)] }),
+            Pattern::Mutable | Pattern::Owned => None,
+        }
+    }
+}
+
+struct Typestate {
+    builder_name: Ident,
+    build_fn_name: Ident,
+    generic_args: Vec<proc_macro2::TokenStream>,
+    slots: Vec<proc_macro2::TokenStream>,
+    fields: Vec<TypestateField>,
+    set: Ident,
+    unset: Ident,
+}
+
+struct TypestateField {
+    ident: Ident,
+    setter: Ident,
+    param: Ident,
+    bound: Ident,
+}
+
+impl Typestate {
+    fn new(builder_name: &Ident, generics: &Generics, attrs: &StructAttrs, fields: &[BuilderField]) -> Self {
+        let generic_args = generics.params.iter().map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote! { #lifetime }
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+        }).collect();
+
+        let slots = fields.iter().map(|BuilderField { ident, attrs, .. }| {
+            let cfg = &attrs.cfg;
+            quote! { #(#cfg)* #ident: builder.#ident }
+        }).collect();
+
+        let fields = fields.iter().filter(|field| field.is_required()).map(|BuilderField { ident, attrs: field_attrs, .. }| {
+            let camel = to_camel_case(&ident.unraw().to_string());
+            TypestateField {
+                ident: ident.clone(),
+                setter: field_attrs.setter.or(&attrs.setter).setter_name(ident),
+                param: Ident::new(&format!("__{}", camel), Span::call_site()),
+                bound: Ident::new(&format!("{}Has{}", builder_name, camel), Span::call_site()),
+            }
+        }).collect();
+
+        Typestate {
+            builder_name: builder_name.clone(),
+            build_fn_name: attrs.build_fn.name.clone().unwrap_or_else(|| format_ident!("build")),
+            generic_args,
+            slots,
+            fields,
+            set: Ident::new(&format!("{}Set", builder_name), Span::call_site()),
+            unset: Ident::new(&format!("{}Unset", builder_name), Span::call_site()),
+        }
+    }
+
+    fn params(&self) -> impl Iterator<Item = GenericParam> + '_ {
+        let unset = &self.unset;
+        self.fields.iter().map(move |field| {
+            let param = &field.param;
+            parse_quote! { #param = #unset }
+        })
+    }
+
+    fn items(&self) -> proc_macro2::TokenStream {
+        let builder_name = &self.builder_name;
+        let build_fn_name = self.build_fn_name.unraw();
+        let set = &self.set;
+        let unset = &self.unset;
+        let bounds = self.fields.iter().map(|field| {
+            let bound = &field.bound;
+            let ident = field.ident.unraw().to_string();
+            let message = format!("`{}::{}` requires field `{}` to be set", builder_name, build_fn_name, ident);
+            let label = format!("missing required field `{}`", ident);
+            let note = format!("call `.{}(...)` before `.{}()`", field.setter.unraw(), build_fn_name);
+            quote! {
+                #[doc(hidden)]
+                #[diagnostic::on_unimplemented(message = #message, label = #label, note = #note)]
+                pub trait #bound {}
+
+                impl #bound for #set {}
+            }
+        });
+
+        quote! {
+            #[doc(hidden)]
+            #[derive(core::fmt::Debug, core::clone::Clone, core::marker::Copy, core::cmp::PartialEq, core::cmp::Eq)]
+            pub struct #set;
+
+            #[doc(hidden)]
+            #[derive(core::fmt::Debug, core::clone::Clone, core::marker::Copy, core::cmp::PartialEq, core::cmp::Eq)]
+            pub struct #unset;
+
+            #(#bounds)*
+        }
+    }
 
-            Some(lit_str.value())
+    fn marker_types(&self) -> impl Iterator<Item = proc_macro2::TokenStream> + '_ {
+        self.fields.iter().map(|field| {
+            let param = &field.param;
+            quote! { #param }
         })
+    }
+
+    fn filled_type(&self) -> proc_macro2::TokenStream {
+        let builder_name = &self.builder_name;
+        let generic_args = &self.generic_args;
+        let states = self.fields.iter().map(|_| &self.set);
+        quote! { #builder_name<#(#generic_args,)* #(#states),*> }
+    }
+
+    fn build_bounds(&self) -> impl Iterator<Item = proc_macro2::TokenStream> + '_ {
+        self.fields.iter().map(|field| {
+            let param = &field.param;
+            let bound = &field.bound;
+            quote! { #param: #bound }
+        })
+    }
+
+    fn setter(&self, name: &Ident, field_ident: &Ident, arg: &SetterArg, assign: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let builder_name = &self.builder_name;
+        let generic_args = &self.generic_args;
+        let states = self.fields.iter().map(|field| {
+            if field.ident == *field_ident {
+                let set = &self.set;
+                quote! { #set }
+            } else {
+                let param = &field.param;
+                quote! { #param }
+            }
+        });
+        let slots = &self.slots;
+        let SetterArg { generics, arg: param, value, .. } = arg;
+        let (ret, tail) = arg.wrap_return(
+            quote! { #builder_name<#(#generic_args,)* #(#states),*> },
+            quote! {
+                #builder_name {
+                    #(#slots,)*
+                    __marker: core::marker::PhantomData,
+                }
+            },
+        );
+
+        quote! {
+            pub fn #name #generics(self, #param) -> #ret {
+                let value = #value;
+                let mut builder = self;
+                #assign
+                #tail
+            }
+        }
+    }
 }
 
-fn get_unexpected_attributes(attrs: &[Attribute]) -> Option<syn::Error> {
-    attrs
-        .first()
-        .filter(|attr|
-            attr.parse_args::<MetaNameValue>()
-                .ok()
-                .is_some_and(|name_value| !name_value.path.is_ident("each")))
-        .map(|attr| syn::Error::new(attr.meta.span(), "expected `builder(each = \"...\")`"))
+struct SetterArg {
+    generics: Option<proc_macro2::TokenStream>,
+    arg: proc_macro2::TokenStream,
+    value: proc_macro2::TokenStream,
+    error: Option<proc_macro2::TokenStream>,
+}
+
+impl SetterArg {
+    fn new(name: &Ident, ty: &Type, into: bool) -> Self {
+        if into {
+            SetterArg {
+                generics: Some(quote! { <__Value: core::convert::Into<#ty>> }),
+                arg: quote! { #name: __Value },
+                value: quote! { core::convert::Into::into(#name) },
+                error: None,
+            }
+        } else {
+            SetterArg {
+                generics: None,
+                arg: quote! { #name: #ty },
+                value: quote! { #name },
+                error: None,
+            }
+        }
+    }
+
+    fn try_new(name: &Ident, ty: &Type) -> Self {
+        SetterArg {
+            generics: Some(quote! { <__Value: core::convert::TryInto<#ty>> }),
+            arg: quote! { #name: __Value },
+            value: quote! { core::convert::TryInto::try_into(#name)? },
+            error: Some(quote! { <__Value as core::convert::TryInto<#ty>>::Error }),
+        }
+    }
+
+    fn new_entry(key_ty: &Type, value_ty: &Type, into: bool) -> Self {
+        if into {
+            SetterArg {
+                generics: Some(quote! { <__Key: core::convert::Into<#key_ty>, __Value: core::convert::Into<#value_ty>> }),
+                arg: quote! { key: __Key, value: __Value },
+                value: quote! { (core::convert::Into::into(key), core::convert::Into::into(value)) },
+                error: None,
+            }
+        } else {
+            SetterArg {
+                generics: None,
+                arg: quote! { key: #key_ty, value: #value_ty },
+                value: quote! { (key, value) },
+                error: None,
+            }
+        }
+    }
+
+    // Only the value is converted fallibly: the key converts with Into, so the
+    // setter's error is the value's conversion error alone.
+    fn try_new_entry(key_ty: &Type, value_ty: &Type) -> Self {
+        SetterArg {
+            generics: Some(quote! { <__Key: core::convert::Into<#key_ty>, __Value: core::convert::TryInto<#value_ty>> }),
+            arg: quote! { key: __Key, value: __Value },
+            value: quote! { (core::convert::Into::into(key), core::convert::TryInto::try_into(value)?) },
+            error: Some(quote! { <__Value as core::convert::TryInto<#value_ty>>::Error }),
+        }
+    }
+
+    fn new_iter(name: &Ident, item_ty: &Type) -> Self {
+        SetterArg {
+            generics: Some(quote! { <__Iter: core::iter::IntoIterator<Item = #item_ty>> }),
+            arg: quote! { #name: __Iter },
+            value: quote! { #name },
+            error: None,
+        }
+    }
+
+    fn wrap_return(&self, ty: proc_macro2::TokenStream, builder: proc_macro2::TokenStream) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        match &self.error {
+            Some(error) => (
+                quote! { core::result::Result<#ty, #error> },
+                quote! { core::result::Result::Ok(#builder) },
+            ),
+            None => (ty, builder),
+        }
+    }
+}
+
+enum EachItem {
+    Value(Box<Type>),
+    Entry(Box<Type>, Box<Type>),
+}
+
+impl EachItem {
+    fn to_type(&self) -> Type {
+        match self {
+            EachItem::Value(ty) => (**ty).clone(),
+            EachItem::Entry(key_ty, value_ty) => parse_quote! { (#key_ty, #value_ty) },
+        }
+    }
+}
+
+/// A run of capitals is one word, so `HTTPServer` becomes `http_server`.
+fn to_snake_case(camel: &str) -> String {
+    let chars = camel.chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (index, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() && index > 0 {
+            let prev = chars[index - 1];
+            let next_is_lower = chars.get(index + 1).is_some_and(|next| next.is_lowercase());
+            if !prev.is_uppercase() && prev != '_' || prev.is_uppercase() && next_is_lower {
+                snake.push('_');
+            }
+        }
+        snake.extend(ch.to_lowercase());
+    }
+    snake
+}
+
+fn to_camel_case(snake: &str) -> String {
+    snake
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn get_each_item(ty: &Type, explicit: Option<&EachItemAttr>) -> EachItem {
+    match explicit {
+        Some(EachItemAttr::Item(item)) => return EachItem::Value(item.clone()),
+        Some(EachItemAttr::Entry(key, value)) => return EachItem::Entry(key.clone(), value.clone()),
+        None => {}
+    }
+
+    let std_collection = std_type_segment(ty).map(|segment| (segment.ident.to_string(), generic_types(segment)));
+    match std_collection.as_ref().map(|(collection, types)| (collection.as_str(), types.as_slice())) {
+        Some(("Vec" | "VecDeque" | "LinkedList" | "HashSet" | "BTreeSet" | "BinaryHeap", [item, ..])) => {
+            return EachItem::Value(Box::new((*item).clone()));
+        }
+        Some(("HashMap" | "BTreeMap", [key, value, ..])) => {
+            return EachItem::Entry(Box::new((*key).clone()), Box::new((*value).clone()));
+        }
+        _ => {}
+    }
+    EachItem::Value(Box::new(parse_quote! { <#ty as core::iter::IntoIterator>::Item }))
 }
@@ -0,0 +1,40 @@
+// Fields marked #[builder(default)] fall back to Default::default() when their
+// setter is never called, and #[builder(default = "...")] evaluates the given
+// expression instead. Either way the field is no longer required by build().
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+    #[builder(default = "8080")]
+    port: u16,
+    #[builder(default)]
+    workers: usize,
+    #[builder(default = "vec![\"localhost\".to_owned()]")]
+    aliases: Vec<String>,
+}
+
+fn main() {
+    let server = Server::builder()
+        .host("example.com".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(server.host, "example.com");
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.workers, 0);
+    assert_eq!(server.aliases, vec!["localhost"]);
+
+    let server = Server::builder()
+        .host("example.com".to_owned())
+        .port(443)
+        .workers(4)
+        .aliases(vec![])
+        .build()
+        .unwrap();
+
+    assert_eq!(server.port, 443);
+    assert_eq!(server.workers, 4);
+    assert!(server.aliases.is_empty());
+}
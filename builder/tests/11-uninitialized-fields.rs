@@ -0,0 +1,32 @@
+// build() reports missing fields through a generated CommandBuilderError enum
+// instead of an opaque boxed error. Every field that was left unset is named,
+// not only the first one encountered.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let err = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err, CommandBuilderError::UninitializedField("args"));
+    assert_eq!(err.to_string(), "`args` must be initialized");
+
+    let err = Command::builder().build().err().unwrap();
+    assert_eq!(
+        err,
+        CommandBuilderError::UninitializedFields(vec!["executable", "args"]),
+    );
+    assert_eq!(err.to_string(), "`executable`, `args` must be initialized");
+
+    let boxed: Box<dyn std::error::Error> = Box::new(err);
+    assert!(boxed.source().is_none());
+}
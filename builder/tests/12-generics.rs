@@ -0,0 +1,60 @@
+// The builder carries over the lifetimes, type parameters, const parameters
+// and where-clause of the struct it was derived from.
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+#[derive(Builder)]
+pub struct Borrowed<'a> {
+    name: &'a str,
+    tags: Option<&'a [&'a str]>,
+}
+
+#[derive(Builder)]
+pub struct Wrapper<T>
+where
+    T: Clone + Debug,
+{
+    value: T,
+    #[builder(each = "extra")]
+    extras: Vec<T>,
+}
+
+#[derive(Builder)]
+pub struct Buffer<const N: usize> {
+    data: [u8; N],
+}
+
+#[derive(Builder)]
+pub struct Request<'a, T: Clone, const N: usize> {
+    body: &'a T,
+    headers: [&'a str; N],
+}
+
+fn main() {
+    let name = String::from("borrowed");
+    let borrowed = Borrowed::builder().name(&name).build().unwrap();
+    assert_eq!(borrowed.name, "borrowed");
+    assert!(borrowed.tags.is_none());
+
+    let wrapper = Wrapper::builder()
+        .value(1u8)
+        .extra(2)
+        .extra(3)
+        .build()
+        .unwrap();
+    assert_eq!(wrapper.value, 1);
+    assert_eq!(wrapper.extras, vec![2, 3]);
+
+    let buffer = Buffer::<4>::builder().data([1, 2, 3, 4]).build().unwrap();
+    assert_eq!(buffer.data, [1, 2, 3, 4]);
+
+    let body = vec![0u8; 3];
+    let request = Request::builder()
+        .body(&body)
+        .headers(["accept", "host"])
+        .build()
+        .unwrap();
+    assert_eq!(request.body.len(), 3);
+    assert_eq!(request.headers.len(), 2);
+}
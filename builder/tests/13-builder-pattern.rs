@@ -0,0 +1,50 @@
+// #[builder(pattern = "...")] selects how setters and build() receive the
+// builder. "owned" setters take and return the builder by value and build()
+// moves the fields out, so field types need not implement Clone. "immutable"
+// setters take &self and return an updated copy of the builder.
+
+use derive_builder::Builder;
+
+pub struct Connection {
+    pub address: String,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Client {
+    connection: Connection,
+    #[builder(each = "header")]
+    headers: Vec<String>,
+    timeout: Option<u64>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Query {
+    table: String,
+    limit: Option<usize>,
+}
+
+fn client_builder() -> ClientBuilder {
+    Client::builder().header("accept".to_owned())
+}
+
+fn main() {
+    let client = client_builder()
+        .connection(Connection {
+            address: "localhost".to_owned(),
+        })
+        .timeout(30)
+        .build()
+        .unwrap();
+    assert_eq!(client.connection.address, "localhost");
+    assert_eq!(client.headers, vec!["accept"]);
+    assert_eq!(client.timeout, Some(30));
+
+    let base = Query::builder().table("users".to_owned());
+    let limited = base.limit(10).build().unwrap();
+    let unlimited = base.build().unwrap();
+    assert_eq!(limited.table, "users");
+    assert_eq!(limited.limit, Some(10));
+    assert_eq!(unlimited.limit, None);
+}
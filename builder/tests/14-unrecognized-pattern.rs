@@ -0,0 +1,11 @@
+// Only the "mutable", "owned" and "immutable" patterns are understood.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "borrowed")]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {}
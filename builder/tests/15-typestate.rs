@@ -0,0 +1,32 @@
+// With #[builder(typestate)] the builder tracks which required fields have
+// been set in its type, so build() is only callable once all of them are.
+// Option fields, each fields and fields with a default remain optional.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command<'a> {
+    executable: &'a str,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    env_clear: bool,
+}
+
+fn main() {
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .env_clear(true)
+        .executable("cargo")
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert!(command.current_dir.is_none());
+    assert!(command.env_clear);
+
+    let partial: CommandBuilder = Command::builder();
+    let _ = partial.current_dir("..".to_owned()).executable("rustc");
+}
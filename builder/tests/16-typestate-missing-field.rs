@@ -0,0 +1,15 @@
+// Calling build() on a typestate builder before every required field is set is
+// a compile error naming the field that is still missing.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let _ = Command::builder().current_dir("..".to_owned()).build();
+}
@@ -0,0 +1,53 @@
+// #[builder(setter(into))] makes a setter generic over Into<T>, either for a
+// single field or, on the struct, as the default for every field.
+// #[builder(setter(strip_option = false))] keeps the full Option<T> as the
+// setter argument so that a field can be reset to None.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(setter(into))]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(setter(into = false))]
+    timeout: u64,
+}
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(setter(into))]
+    url: String,
+    #[builder(setter(strip_option = false))]
+    body: Option<String>,
+    #[builder(setter(into, strip_option = false))]
+    method: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir("..")
+        .timeout(30)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.timeout, 30);
+
+    let request = Request::builder()
+        .url("https://example.com")
+        .body(Some("payload".to_owned()))
+        .body(None)
+        .method(String::from("GET"))
+        .build()
+        .unwrap();
+    assert_eq!(request.url, "https://example.com");
+    assert!(request.body.is_none());
+    assert_eq!(request.method.as_deref(), Some("GET"));
+}
@@ -0,0 +1,69 @@
+// #[builder(try_setter)] generates a fallible try_ setter next to the regular
+// one. It accepts anything implementing TryInto for the argument type and
+// returns the conversion error instead of storing a value. For map entries
+// only the value is converted fallibly; the key takes anything Into the key
+// type.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Even(u32);
+
+impl TryFrom<u32> for Even {
+    type Error = String;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        if value % 2 == 0 {
+            Ok(Even(value))
+        } else {
+            Err(format!("{} is odd", value))
+        }
+    }
+}
+
+#[derive(Builder)]
+pub struct Listener {
+    #[builder(try_setter)]
+    port: u16,
+    #[builder(try_setter)]
+    backlog: Option<u8>,
+    #[builder(each = "worker")]
+    #[builder(try_setter)]
+    workers: Vec<Even>,
+    #[builder(each = "limit", try_setter)]
+    limits: HashMap<String, u8>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Socket {
+    #[builder(try_setter)]
+    port: u16,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let listener = Listener::builder()
+        .try_port(8080u32)?
+        .try_backlog(16i64)?
+        .try_worker(2u32)?
+        .worker(Even(4))
+        .try_limit("connections", 100u32)?
+        .build()?;
+    assert_eq!(listener.port, 8080);
+    assert_eq!(listener.backlog, Some(16));
+    assert_eq!(listener.workers, vec![Even(2), Even(4)]);
+    assert_eq!(listener.limits["connections"], 100);
+
+    let mut builder = Listener::builder();
+    assert!(builder.try_port(70000u32).is_err());
+    assert!(builder.try_backlog(-1i32).is_err());
+    assert_eq!(builder.try_worker(3u32).err().unwrap(), "3 is odd");
+    assert!(builder.try_limit("a".to_owned(), 300u32).is_err());
+
+    let socket = Socket::builder().try_port(443u64)?.build()?;
+    assert_eq!(socket.port, 443);
+
+    Ok(())
+}
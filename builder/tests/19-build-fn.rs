@@ -0,0 +1,79 @@
+// #[builder(build_fn(...))] customises the generated build method. `validate`
+// names a function that checks the builder before the struct is constructed,
+// `name` renames the method, `error` converts the generated error into a
+// caller-defined type through From, and `skip` omits the method entirely.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(build_fn(validate = "validate_range"))]
+pub struct Range {
+    min: u32,
+    max: u32,
+}
+
+fn validate_range(builder: &RangeBuilder) -> Result<(), String> {
+    match (builder.min_value(), builder.max_value()) {
+        (Some(min), Some(max)) if min > max => Err(format!("min {} exceeds max {}", min, max)),
+        _ => Ok(()),
+    }
+}
+
+impl RangeBuilder {
+    fn min_value(&self) -> Option<u32> {
+        self.min
+    }
+
+    fn max_value(&self) -> Option<u32> {
+        self.max
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    Builder(String),
+}
+
+impl From<ConfigBuilderError> for ConfigError {
+    fn from(err: ConfigBuilderError) -> Self {
+        ConfigError::Builder(err.to_string())
+    }
+}
+
+#[derive(Builder)]
+#[builder(build_fn(name = "finish", error = "ConfigError"))]
+pub struct Config {
+    path: String,
+}
+
+#[derive(Builder)]
+#[builder(build_fn(skip))]
+pub struct Manual {
+    value: u32,
+}
+
+impl ManualBuilder {
+    pub fn build(&mut self) -> Manual {
+        Manual {
+            value: self.value.unwrap_or(42),
+        }
+    }
+}
+
+fn main() {
+    let range = Range::builder().min(1).max(2).build().unwrap();
+    assert_eq!((range.min, range.max), (1, 2));
+
+    let err = Range::builder().min(3).max(2).build().err().unwrap();
+    assert_eq!(
+        err,
+        RangeBuilderError::ValidationError("min 3 exceeds max 2".to_owned()),
+    );
+
+    let config = Config::builder().path("a.toml".to_owned()).finish().unwrap();
+    assert_eq!(config.path, "a.toml");
+    let err = Config::builder().finish().err().unwrap();
+    assert_eq!(err, ConfigError::Builder("`path` must be initialized".to_owned()));
+
+    assert_eq!(Manual::builder().build().value, 42);
+}
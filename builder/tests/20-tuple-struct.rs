@@ -0,0 +1,32 @@
+// Tuple structs get positional setters named `_0`, `_1` and so on, unless a
+// field is given a name with #[builder(name = "...")].
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Point(i32, i32, Option<i32>);
+
+#[derive(Builder)]
+pub struct Rgb(
+    #[builder(name = "red")] u8,
+    #[builder(name = "green")] u8,
+    #[builder(name = "blue")]
+    #[builder(default)]
+    u8,
+);
+
+#[derive(Builder)]
+pub struct Marker;
+
+fn main() {
+    let point = Point::builder()._0(1)._1(2).build().unwrap();
+    assert_eq!((point.0, point.1, point.2), (1, 2, None));
+
+    let err = Point::builder()._1(2).build().err().unwrap();
+    assert_eq!(err, PointBuilderError::UninitializedField("_0"));
+
+    let color = Rgb::builder().red(255).green(128).build().unwrap();
+    assert_eq!((color.0, color.1, color.2), (255, 128, 0));
+
+    let Marker = Marker::builder().build().unwrap();
+}
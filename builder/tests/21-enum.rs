@@ -0,0 +1,35 @@
+// Enums get one builder per variant, created through a snake_case
+// `<variant>_builder` function on the enum. A run of capitals in the variant
+// name counts as one word.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Shape<T: Clone> {
+    Circle {
+        radius: T,
+        label: Option<String>,
+    },
+    Rectangle(T, T),
+    Empty,
+    HTTPServer {
+        port: u16,
+    },
+}
+
+fn main() {
+    let circle = Shape::circle_builder().radius(2.0).build().unwrap();
+    assert_eq!(circle, Shape::Circle { radius: 2.0, label: None });
+
+    let rectangle = Shape::rectangle_builder()._0(1)._1(2).build().unwrap();
+    assert_eq!(rectangle, Shape::Rectangle(1, 2));
+
+    let empty = Shape::<u8>::empty_builder().build().unwrap();
+    assert_eq!(empty, Shape::Empty);
+
+    let server = Shape::<u8>::http_server_builder().port(80).build().unwrap();
+    assert_eq!(server, Shape::HTTPServer { port: 80 });
+
+    let err = Shape::<u8>::rectangle_builder()._0(1).build().err().unwrap();
+    assert_eq!(err, ShapeRectangleBuilderError::UninitializedField("_1"));
+}
@@ -0,0 +1,11 @@
+// Unions are not supported and are reported as an error on the union keyword.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
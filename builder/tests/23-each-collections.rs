@@ -0,0 +1,67 @@
+// #[builder(each = "...")] works with any collection that implements Default
+// and Extend. Map types get an item setter taking a key and a value, and every
+// `each` field also gets an `extend_<field>` method accepting an iterator.
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+#[derive(Default)]
+pub struct Lines(Vec<String>);
+
+impl Extend<String> for Lines {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for Lines {
+    type Item = String;
+    type IntoIter = std::vec::IntoIter<String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Request {
+    #[builder(each = "header")]
+    headers: HashMap<String, String>,
+    #[builder(each = "param")]
+    #[builder(setter(into))]
+    params: BTreeMap<String, u32>,
+    #[builder(each = "tag")]
+    tags: HashSet<&'static str>,
+    #[builder(each = "hop")]
+    hops: VecDeque<u8>,
+    #[builder(each = "line")]
+    lines: Lines,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+}
+
+fn main() {
+    let request = Request::builder()
+        .header("accept".to_owned(), "text/html".to_owned())
+        .param("page", 2u8)
+        .extend_params(vec![("limit".to_owned(), 10)])
+        .tag("a")
+        .tag("a")
+        .hop(1)
+        .extend_hops([2, 3])
+        .line("first".to_owned())
+        .extend_lines(vec!["second".to_owned()])
+        .arg("--verbose".to_owned())
+        .extend_args(vec!["--release".to_owned()])
+        .build()
+        .unwrap();
+
+    assert_eq!(request.headers["accept"], "text/html");
+    assert_eq!(request.params["page"], 2);
+    assert_eq!(request.params["limit"], 10);
+    assert_eq!(request.tags.len(), 1);
+    assert_eq!(request.hops, [1, 2, 3]);
+    assert_eq!(request.lines.0, vec!["first", "second"]);
+    assert_eq!(request.args, vec!["--verbose", "--release"]);
+}
@@ -0,0 +1,33 @@
+// Every #[builder(...)] attribute on a field is read, not just the first
+// attribute, and several keys can be combined in a single attribute.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    /// Arguments passed to the executable.
+    #[builder(each = "arg", default)]
+    args: Vec<String>,
+    #[doc = "Environment variables."]
+    #[builder(each = "env")]
+    #[builder(setter(into))]
+    env: Vec<String>,
+    #[builder(default, into)]
+    current_dir: String,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .env("RUST_LOG=info".to_owned())
+        .current_dir("..")
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.env, vec!["RUST_LOG=info"]);
+    assert_eq!(command.current_dir, "..");
+}
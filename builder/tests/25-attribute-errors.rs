@@ -0,0 +1,25 @@
+// Each unknown or duplicate key is reported with its own span, and parsing
+// continues past the first error. Unknown keys close to a known one suggest
+// it; others list the keys that are accepted.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(eac = "arg", default, frobnicate)]
+    args: Vec<String>,
+    #[builder(defualt, skp)]
+    current_dir: Option<String>,
+    #[builder(each = "env")]
+    #[builder(each = "var")]
+    env: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(frobnicate, patern = "owned")]
+pub struct Config {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,47 @@
+// The generated builder can be renamed, given a narrower visibility, and
+// decorated with extra derives and arbitrary attributes.
+
+mod request {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    #[builder(name = "RequestOptions", vis = "pub(crate)")]
+    #[builder(derive(Debug, Clone, PartialEq), struct_attr(must_use))]
+    pub struct Options {
+        pub url: String,
+        pub retries: Option<u32>,
+    }
+}
+
+mod response {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    #[builder(name = "ResponseOptions", pattern = "immutable", derive(Clone, Debug))]
+    pub struct Options {
+        pub status: u16,
+    }
+}
+
+use request::RequestOptions;
+use response::ResponseOptions;
+
+fn main() {
+    let mut builder: RequestOptions = request::Options::builder();
+    builder.url("https://example.com".to_owned());
+    let snapshot = builder.clone();
+    builder.retries(3);
+    assert_ne!(snapshot, builder);
+    assert!(format!("{:?}", builder).starts_with("RequestOptions"));
+
+    let options = builder.build().unwrap();
+    assert_eq!(options.url, "https://example.com");
+    assert_eq!(options.retries, Some(3));
+
+    let err: request::RequestOptionsError = request::Options::builder().build().err().unwrap();
+    assert_eq!(err.to_string(), "`url` must be initialized");
+
+    let builder: ResponseOptions = response::Options::builder().status(200);
+    assert_eq!(builder.clone().build().unwrap().status, 200);
+    assert_eq!(builder.build().unwrap().status, 200);
+}
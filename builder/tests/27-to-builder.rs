@@ -0,0 +1,52 @@
+// A built value can be turned back into a builder, either by value through
+// `From` or by reference with `to_builder()`, with every field pre-populated.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(each = "alias")]
+    aliases: Vec<String>,
+    timeout: Option<u64>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(typestate)]
+pub struct Point<T> {
+    x: T,
+    y: T,
+}
+
+// `to_builder` is only callable on Clone types, but `From` is always there.
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Handle {
+    file: std::fs::File,
+}
+
+fn main() {
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .port(80)
+        .alias("www".to_owned())
+        .timeout(30)
+        .build()
+        .unwrap();
+
+    let moved = server.to_builder().port(8080).alias("api".to_owned()).build().unwrap();
+    assert_eq!(moved.host, "localhost");
+    assert_eq!(moved.port, 8080);
+    assert_eq!(moved.aliases, vec!["www", "api"]);
+    assert_eq!(moved.timeout, Some(30));
+
+    let unchanged = ServerBuilder::from(server.clone()).build().unwrap();
+    assert_eq!(unchanged, server);
+
+    let point = Point::builder().x(1).y(2).build().unwrap();
+    let point = PointBuilder::from(point).y(5).build().unwrap();
+    assert_eq!(point, Point { x: 1, y: 5 });
+
+    let _: fn(Handle) -> HandleBuilder = HandleBuilder::from;
+}
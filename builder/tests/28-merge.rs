@@ -0,0 +1,45 @@
+// Builders can be layered with `merge`: every field set in the other builder
+// overrides the same field in this one. `each` collections are appended by
+// default, or replaced with #[builder(merge = "replace")].
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Config {
+    host: String,
+    port: u16,
+    log_level: Option<String>,
+    #[builder(each = "plugin")]
+    plugins: Vec<String>,
+    #[builder(each = "include", merge = "replace")]
+    includes: Vec<String>,
+}
+
+fn main() {
+    let mut defaults = Config::builder();
+    defaults
+        .host("localhost".to_owned())
+        .port(80)
+        .plugin("core".to_owned())
+        .include("/etc/app.conf".to_owned());
+
+    let mut file = Config::builder();
+    file.port(8080)
+        .log_level("info".to_owned())
+        .plugin("metrics".to_owned());
+
+    let mut cli = Config::builder();
+    cli.include("./app.conf".to_owned());
+
+    let config = defaults.merge(file).merge(cli).build().unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.log_level.as_deref(), Some("info"));
+    assert_eq!(config.plugins, vec!["core", "metrics"]);
+    assert_eq!(config.includes, vec!["./app.conf"]);
+
+    let mut empty = Config::builder();
+    empty.include("/etc/app.conf".to_owned());
+    let config = empty.merge(Config::builder()).host(String::new()).port(0).build().unwrap();
+    assert_eq!(config.includes, vec!["/etc/app.conf"]);
+}
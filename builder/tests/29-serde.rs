@@ -0,0 +1,60 @@
+// With the `serde` feature enabled, #[builder(deserialize)] makes the builder
+// implement Deserialize with every field optional, so a partial document
+// deserializes into a partial builder and missing required fields are reported
+// by `build()`. A missing sub_builder field starts from an empty nested
+// builder.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(deserialize)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[serde(rename = "cwd")]
+    current_dir: Option<String>,
+    #[builder(default = "30")]
+    timeout: u64,
+    #[builder(sub_builder)]
+    retry: Retry,
+}
+
+#[derive(Builder, Debug)]
+#[builder(deserialize)]
+pub struct Retry {
+    #[builder(default = "3")]
+    attempts: u32,
+}
+
+#[derive(Builder, Debug)]
+#[builder(deserialize)]
+pub enum Shape {
+    Circle { radius: f64 },
+}
+
+fn main() {
+    let mut builder: CommandBuilder = serde_json::from_str(
+        r#"{ "executable": "cargo", "args": ["build", "--release"], "cwd": ".." }"#,
+    )
+    .unwrap();
+    let command = builder.build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.timeout, 30);
+    assert_eq!(command.retry.attempts, 3);
+
+    let mut builder: CommandBuilder = serde_json::from_str(r#"{ "timeout": 5 }"#).unwrap();
+    assert_eq!(builder.build().unwrap_err().to_string(), "`executable` must be initialized");
+    let command = builder.executable("make".to_owned()).build().unwrap();
+    assert!(command.args.is_empty());
+    assert_eq!(command.timeout, 5);
+
+    let mut builder: CommandBuilder =
+        serde_json::from_str(r#"{ "executable": "make", "retry": { "attempts": 1 } }"#).unwrap();
+    assert_eq!(builder.build().unwrap().retry.attempts, 1);
+
+    let mut builder: ShapeCircleBuilder = serde_json::from_str(r#"{ "radius": 2.0 }"#).unwrap();
+    assert!(matches!(builder.build().unwrap(), Shape::Circle { radius } if radius == 2.0));
+}
@@ -0,0 +1,53 @@
+// #[builder(env = "VAR")] reads a field from the environment when its setter
+// was not called. The setter takes precedence over the variable, which takes
+// precedence over `default`. Values that fail to parse become a build error
+// naming both the field and the variable.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    #[builder(env = "TEST_SERVER_HOST")]
+    host: String,
+    #[builder(env = "TEST_SERVER_PORT", default = "80")]
+    port: u16,
+    #[builder(env = "TEST_SERVER_WORKERS")]
+    workers: Option<usize>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Client {
+    #[builder(env = "TEST_CLIENT_URL")]
+    url: String,
+}
+
+fn main() {
+    let err = Server::builder().build().unwrap_err();
+    assert_eq!(err, ServerBuilderError::UninitializedField("host"));
+
+    std::env::set_var("TEST_SERVER_HOST", "example.com");
+    std::env::set_var("TEST_SERVER_WORKERS", "4");
+    let server = Server::builder().build().unwrap();
+    assert_eq!(server.host, "example.com");
+    assert_eq!(server.port, 80);
+    assert_eq!(server.workers, Some(4));
+
+    std::env::set_var("TEST_SERVER_PORT", "8080");
+    let server = Server::builder().host("localhost".to_owned()).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+
+    std::env::set_var("TEST_SERVER_PORT", "eighty");
+    let err = Server::builder().build().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`port` could not be read from environment variable `TEST_SERVER_PORT`: invalid digit found in string",
+    );
+    let server = Server::builder().port(443).build().unwrap();
+    assert_eq!(server.port, 443);
+
+    assert!(Client::builder().build().is_err());
+    std::env::set_var("TEST_CLIENT_URL", "https://example.com");
+    assert_eq!(Client::builder().build().unwrap().url, "https://example.com");
+}
@@ -0,0 +1,121 @@
+// #[builder(sub_builder)] keeps a nested builder for a field whose type also
+// derives Builder. It is reachable through `<field>_mut()` or a closure taking
+// setter, and errors from the nested build() name the full field path. The
+// nested type may rename its build method or use its own error type. A nested
+// builder with the owned pattern is passed to the closure by value and the
+// closure returns it.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Tls {
+    cert: String,
+    key: String,
+    #[builder(each = "cipher")]
+    ciphers: Vec<String>,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(build_fn(name = "finish", error = "InvalidLimits"))]
+pub struct Limits {
+    #[builder(default = "16")]
+    connections: u32,
+}
+
+#[derive(Debug)]
+pub struct InvalidLimits;
+
+impl From<LimitsBuilderError> for InvalidLimits {
+    fn from(_: LimitsBuilderError) -> Self {
+        InvalidLimits
+    }
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Server {
+    host: String,
+    #[builder(sub_builder)]
+    tls: Tls,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Pair(#[builder(sub_builder)] Tls, u32);
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct App {
+    name: String,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Credentials {
+    user: String,
+    password: String,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Login {
+    #[builder(sub_builder)]
+    credentials: Credentials,
+    #[builder(default)]
+    remember: bool,
+}
+
+fn main() {
+    let mut builder = Server::builder();
+    builder.host("localhost".to_owned());
+    builder.tls_mut().cert("cert.pem".to_owned());
+    builder.tls(|tls| {
+        tls.key("key.pem".to_owned()).cipher("AES".to_owned());
+    });
+    let server = builder.build().unwrap();
+    assert_eq!(server.tls.cert, "cert.pem");
+    assert_eq!(server.tls.ciphers, vec!["AES"]);
+    assert_eq!(server.limits.connections, 16);
+    assert!(Limits::builder().finish().is_ok());
+
+    let err = App::builder()
+        .name("app".to_owned())
+        .server(|server| {
+            server.host("localhost".to_owned()).tls(|tls| {
+                tls.key("key.pem".to_owned());
+            });
+        })
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        AppBuilderError::Server(ServerBuilderError::Tls(TlsBuilderError::UninitializedField("cert"))),
+    );
+    assert_eq!(err.to_string(), "`server.tls.cert` must be initialized");
+    assert!(std::error::Error::source(&err).is_some());
+
+    let mut pair = Pair::builder();
+    pair._1(2)._0(|tls| {
+        tls.key("key.pem".to_owned());
+    });
+    let err = pair.build().unwrap_err();
+    assert_eq!(err, PairBuilderError::Field0(TlsBuilderError::UninitializedField("cert")));
+    pair._0_mut().cert("cert.pem".to_owned());
+    assert_eq!(pair.build().unwrap().0.cert, "cert.pem");
+
+    let login = Login::builder()
+        .credentials(|credentials| credentials.user("admin".to_owned()))
+        .credentials(|credentials| credentials.password("secret".to_owned()))
+        .build()
+        .unwrap();
+    assert_eq!(login.credentials.user, "admin");
+    assert_eq!(login.credentials.password, "secret");
+
+    let mut overlay = Server::builder();
+    overlay.tls_mut().cert("other.pem".to_owned());
+    let merged = server.to_builder().merge(overlay).build().unwrap();
+    assert_eq!(merged.tls.cert, "other.pem");
+    assert_eq!(merged.tls.key, "key.pem");
+}
@@ -0,0 +1,84 @@
+// #[builder(skip)] fields get no setter and are filled from Default or their
+// `default` expression. A struct-level #[builder(default)] starts build() from
+// the target's Default impl and only overrides the fields that were set; items
+// added to an `each` field replace the default collection. A generic parameter
+// used only by a skipped field is still allowed.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+#[derive(Builder, Debug)]
+pub struct Client {
+    url: String,
+    #[builder(skip)]
+    cache: HashMap<String, String>,
+    #[builder(skip, default = "3")]
+    retries: u32,
+}
+
+#[derive(Builder, Debug)]
+pub struct Id<T> {
+    value: u64,
+    #[builder(skip)]
+    kind: PhantomData<T>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(default)]
+pub struct Config {
+    host: String,
+    port: u16,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    user: Option<String>,
+    #[builder(default = "1")]
+    workers: usize,
+    #[builder(skip)]
+    version: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            host: "localhost".to_owned(),
+            port: 80,
+            tags: vec!["default".to_owned()],
+            user: Some("admin".to_owned()),
+            workers: 8,
+            version: 2,
+        }
+    }
+}
+
+#[derive(Builder, Debug, Default, PartialEq)]
+#[builder(default)]
+pub struct Pair(u8, #[builder(skip, default = "7")] u8);
+
+fn main() {
+    let client = Client::builder().url("https://example.com".to_owned()).build().unwrap();
+    assert!(client.cache.is_empty());
+    assert_eq!(client.retries, 3);
+
+    let id: Id<Client> = Id::builder().value(1).build().unwrap();
+    assert_eq!(id.value, 1);
+
+    let config = Config::builder().build().unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.workers, 1);
+    assert_eq!(config.version, 2);
+    assert_eq!(config.tags, vec!["default"]);
+
+    let config = Config::builder()
+        .port(8080)
+        .tag("web".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.tags, vec!["web"]);
+    assert_eq!(config.user.as_deref(), Some("admin"));
+
+    assert_eq!(Pair::builder().build().unwrap(), Pair(0, 7));
+    assert_eq!(Pair::builder()._0(1).build().unwrap(), Pair(1, 7));
+}
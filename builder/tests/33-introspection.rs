@@ -0,0 +1,46 @@
+// Builders can be inspected and reset: `get_<field>()` returns the current
+// value, `is_<field>_set()` and `clear_<field>()` query and reset a slot, and
+// `unset_fields()` lists the required fields that are still missing.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(default = "10")]
+    timeout: u64,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    assert_eq!(builder.unset_fields(), vec!["executable"]);
+    assert!(!builder.is_executable_set());
+    assert_eq!(builder.get_executable(), None);
+
+    builder.executable("cargo".to_owned()).arg("build".to_owned()).current_dir("..".to_owned());
+    assert!(builder.unset_fields().is_empty());
+    assert_eq!(builder.get_executable().map(String::as_str), Some("cargo"));
+    assert_eq!(builder.get_args(), &vec!["build".to_owned()]);
+    assert_eq!(builder.get_current_dir().map(String::as_str), Some(".."));
+    assert!(!builder.is_timeout_set());
+
+    builder.clear_executable().clear_args().clear_current_dir();
+    assert_eq!(builder.unset_fields(), vec!["executable"]);
+    assert!(builder.get_args().is_empty());
+    assert!(!builder.is_current_dir_set());
+
+    let builder = Server::builder().host("localhost".to_owned()).port(80);
+    assert!(builder.unset_fields().is_empty());
+    let builder = builder.clear_host().clear_port();
+    assert_eq!(builder.unset_fields(), vec!["host", "port"]);
+}
@@ -0,0 +1,109 @@
+// #[builder] on a function generates a builder whose setters match the
+// parameters and whose `call()` method invokes the function. On an impl block
+// it does the same for every method marked #[builder], and `fn new` becomes
+// `Type::builder()`. Async functions get an async `call()`. Function builders
+// use the owned pattern unless told otherwise, so `call()` moves the arguments
+// into the function and the future of an async `call()` owns them.
+
+use derive_builder::builder;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+#[builder]
+fn connect(
+    host: &str,
+    port: u16,
+    user: Option<String>,
+    #[builder(each = "option")] options: Vec<String>,
+    #[builder(default = "30")] timeout: u64,
+) -> String {
+    format!("{}@{}:{} {:?} {}", user.unwrap_or_default(), host, port, options, timeout)
+}
+
+#[builder(build_fn(name = "run"))]
+fn sum<T: std::ops::Add<Output = T>>(a: T, b: T) -> T {
+    a + b
+}
+
+#[builder]
+async fn fetch(url: String, #[builder(default)] retries: u32) -> (String, u32) {
+    (url, retries)
+}
+
+pub struct Job {
+    id: u32,
+}
+
+#[builder]
+fn run(job: Job, #[builder(default)] priority: u8) -> (u32, u8) {
+    (job.id, priority)
+}
+
+#[builder(pattern = "mutable")]
+fn greet(name: String) -> String {
+    format!("hello {}", name)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[builder]
+impl Server {
+    #[builder]
+    pub fn new(host: String, #[builder(default = "80")] port: u16) -> Self {
+        Server { host, port }
+    }
+
+    #[builder]
+    pub fn local(port: u16) -> Self {
+        Server::new("localhost".to_owned(), port)
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn main() {
+    let host = String::from("example.com");
+    let line = connect_builder()
+        .host(&host)
+        .port(22)
+        .user("root".to_owned())
+        .option("-v".to_owned())
+        .call()
+        .unwrap();
+    assert_eq!(line, r#"root@example.com:22 ["-v"] 30"#);
+
+    let err = connect_builder().port(22).call().unwrap_err();
+    assert_eq!(err, ConnectBuilderError::UninitializedField("host"));
+
+    assert_eq!(sum_builder().a(1).b(2).run().unwrap(), 3);
+
+    let fetch = fetch_builder().url("https://example.com".to_owned()).call();
+    let fetched = block_on(fetch).unwrap();
+    assert_eq!(fetched, ("https://example.com".to_owned(), 0));
+
+    assert_eq!(run_builder().job(Job { id: 7 }).call().unwrap(), (7, 0));
+
+    let mut greeting = greet_builder();
+    greeting.name("world".to_owned());
+    assert_eq!(greeting.call().unwrap(), "hello world");
+    assert_eq!(greeting.call().unwrap(), "hello world");
+
+    let server = Server::builder().host("example.com".to_owned()).call().unwrap();
+    assert_eq!(server, Server { host: "example.com".to_owned(), port: 80 });
+    let server = Server::local_builder().port(8080).call().unwrap();
+    assert_eq!(server, Server::new("localhost".to_owned(), 8080));
+    let _: ServerLocalBuilder = Server::local_builder();
+}
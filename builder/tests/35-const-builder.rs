@@ -0,0 +1,91 @@
+// #[builder(const)] makes builder(), the setters and the owned build() const
+// fns, so a builder can initialize a const or static. Fields that are still
+// unset panic inside build(), which turns into a compile error in const
+// context. Fields whose types need dropping work too, but a const setter
+// cannot drop the value it replaces: setting such a field twice leaks the
+// first value, as `.name("first").name("second")` below does.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const)]
+pub struct Limits {
+    name: &'static str,
+    max_connections: u32,
+    timeout: Option<u64>,
+    #[builder(default = "8")]
+    workers: usize,
+    #[builder(skip, default = "[0; 2]")]
+    reserved: [u8; 2],
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const)]
+pub struct Point(i32, #[builder(setter(strip_option = false))] Option<i32>);
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const)]
+pub struct Cfg {
+    name: String,
+    n: u32,
+    #[builder(default = "Vec::new()")]
+    tags: Vec<String>,
+    comment: Option<String>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const)]
+pub struct Cursor {
+    this: u32,
+    other: u32,
+}
+
+const CURSOR: Cursor = Cursor::builder().this(1).other(2).build();
+
+const CFG: Cfg = Cfg::builder().name(String::new()).n(3).build();
+
+const DEFAULT_LIMITS: Limits = Limits::builder()
+    .name("default")
+    .max_connections(64)
+    .build();
+
+static STRICT_LIMITS: Limits = Limits::builder()
+    .name("strict")
+    .max_connections(4)
+    .timeout(30)
+    .workers(1)
+    .build();
+
+const ORIGIN: Point = Point::builder()._0(0)._1(None).build();
+
+fn main() {
+    assert_eq!(
+        DEFAULT_LIMITS,
+        Limits {
+            name: "default",
+            max_connections: 64,
+            timeout: None,
+            workers: 8,
+            reserved: [0; 2],
+        }
+    );
+    assert_eq!(STRICT_LIMITS.timeout, Some(30));
+    assert_eq!(STRICT_LIMITS.workers, 1);
+    assert_eq!(ORIGIN, Point(0, None));
+
+    let builder = Limits::builder().name("runtime").max_connections(1);
+    assert_eq!(builder.get_name(), Some(&"runtime"));
+    assert_eq!(builder.build().workers, 8);
+
+    assert_eq!(CURSOR, Cursor { this: 1, other: 2 });
+    assert_eq!(CFG, Cfg { name: String::new(), n: 3, tags: Vec::new(), comment: None });
+    let cfg = Cfg::builder()
+        .name("first".to_owned())
+        .name("second".to_owned())
+        .n(1)
+        .tags(vec!["a".to_owned()])
+        .comment("runtime".to_owned())
+        .build();
+    assert_eq!(cfg.name, "second");
+    assert_eq!(cfg.tags, ["a"]);
+}
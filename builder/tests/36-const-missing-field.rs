@@ -0,0 +1,17 @@
+// In a const context, building a #[builder(const)] builder with a missing
+// field fails at compile time and the panic names the field.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Limits {
+    name: &'static str,
+    max_connections: u32,
+}
+
+const LIMITS: Limits = Limits::builder().name("default").build();
+
+fn main() {
+    let _ = LIMITS.max_connections;
+}
@@ -0,0 +1,60 @@
+// `cfg` and `cfg_attr` on a #[builder] function parameter are carried onto the
+// builder slot, its setters and the call, so the builder compiles whether or
+// not the parameter is configured in. On derived structs rustc has already
+// applied them. `doc` and `deprecated` are copied onto the field's setters.
+
+#![deny(warnings)]
+
+use derive_builder::{builder, Builder};
+
+#[builder]
+fn connect(
+    host: &str,
+    #[cfg(any())] certificate: String,
+    #[cfg(not(any()))]
+    #[builder(default = "8080")]
+    port: u16,
+    #[cfg(any())]
+    #[builder(default)]
+    timeout: u64,
+) -> String {
+    format!("{}:{}", host, port)
+}
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    /// Address the server listens on.
+    address: String,
+    #[cfg_attr(not(any()), builder(default = "8080"))]
+    port: u16,
+    #[deprecated(note = "use `headers` instead")]
+    #[builder(default)]
+    legacy_header: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+pub struct Pair(#[cfg(not(any()))] u8, u8);
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Endpoint {
+    #[cfg(not(any()))]
+    path: String,
+}
+
+fn main() {
+    assert_eq!(connect_builder().host("localhost").call().unwrap(), "localhost:8080");
+
+    let server = Server::builder()
+        .address("localhost".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.address, "localhost");
+    assert_eq!(server.port, 8080);
+
+    let pair = Pair::builder()._0(1)._1(2).build().unwrap();
+    assert_eq!((pair.0, pair.1), (1, 2));
+
+    let endpoint = Endpoint::builder().path("/".to_owned()).build().unwrap();
+    assert_eq!(endpoint.path, "/");
+}
@@ -0,0 +1,63 @@
+// #[builder(setter(prefix = "..."))] on the struct prefixes every setter named
+// after its field, and #[builder(setter(name = "..."))] renames a single
+// setter. Fields with `each` also get a setter for the whole collection
+// whenever its name differs from the item setter.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder, Debug)]
+#[builder(setter(prefix = "with"))]
+pub struct Server {
+    host: String,
+    #[builder(setter(into))]
+    port: u16,
+    #[builder(setter(name = "timeout_secs"))]
+    timeout: Option<u64>,
+    #[builder(each = "header")]
+    headers: Vec<String>,
+    #[builder(each = "env", try_setter)]
+    env: HashMap<String, String>,
+}
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: Vec<String>,
+    #[builder(setter(name = "dir"))]
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let server = Server::builder()
+        .with_host("localhost".to_owned())
+        .with_port(8080u16)
+        .timeout_secs(30)
+        .header("x-trace".to_owned())
+        .with_headers(vec!["accept".to_owned(), "x-forwarded".to_owned()])
+        .env("PATH".to_owned(), "/bin".to_owned())
+        .try_with_env([("HOME".to_owned(), "/root".to_owned())])
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.timeout, Some(30));
+    assert_eq!(server.headers, ["accept", "x-forwarded"]);
+    assert_eq!(server.env.len(), 1);
+    assert_eq!(server.env["HOME"], "/root");
+
+    let command = Command::builder()
+        .arg("-v".to_owned())
+        .args(vec!["build".to_owned(), "--release".to_owned()])
+        .arg("--locked".to_owned())
+        .env("CARGO=1".to_owned())
+        .dir("/tmp".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.args, ["build", "--release", "--locked"]);
+    assert_eq!(command.env, ["CARGO=1"]);
+    assert_eq!(command.current_dir.as_deref(), Some("/tmp"));
+}
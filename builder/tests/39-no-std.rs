@@ -0,0 +1,78 @@
+// #[builder(no_std)] generates code that only uses `core::` and `alloc::`
+// paths, so the builder works in a #![no_std] crate that links alloc.
+// #[builder(no_alloc)] goes further: the error type reports only the first
+// missing field and validation messages are `&'static str`.
+//
+// Shadowing `std` below makes any generated `std::` path fail to resolve.
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use derive_builder::Builder;
+
+#[allow(dead_code)]
+mod std {}
+
+#[derive(Builder, Debug)]
+#[builder(no_std)]
+pub struct Packet {
+    id: u16,
+    payload: alloc::vec::Vec<u8>,
+    #[builder(default = "64")]
+    ttl: u8,
+    #[builder(sub_builder)]
+    header: Header,
+}
+
+#[derive(Builder, Debug)]
+#[builder(no_std)]
+pub struct Header {
+    version: u8,
+    flags: Option<u8>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(no_alloc, build_fn(validate = "Pin::validate"))]
+pub struct Pin {
+    number: u8,
+    mode: u8,
+    pull_up: Option<bool>,
+}
+
+impl Pin {
+    fn validate(builder: &PinBuilder) -> Result<(), &'static str> {
+        match builder.number {
+            Some(number) if number > 40 => Err("pin number out of range"),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn main() {
+    let packet = Packet::builder()
+        .id(7)
+        .payload(alloc::vec![1, 2, 3])
+        .header(|header| {
+            header.version(1);
+        })
+        .build()
+        .unwrap();
+    assert_eq!(packet.ttl, 64);
+    assert_eq!(packet.header.version, 1);
+    assert_eq!(packet.header.flags, None);
+
+    let err = Packet::builder().id(1).build().unwrap_err();
+    assert_eq!(err.to_string(), "`payload` must be initialized");
+    let err = Packet::builder().id(1).payload(alloc::vec![]).build().unwrap_err();
+    assert_eq!(err.to_string(), "`header.version` must be initialized");
+
+    let pin = Pin::builder().number(4).mode(1).build().unwrap();
+    assert_eq!(pin.number, 4);
+    assert_eq!(pin.pull_up, None);
+
+    let err = Pin::builder().build().unwrap_err();
+    assert_eq!(err, PinBuilderError::UninitializedField("number"));
+    let err = Pin::builder().number(41).mode(0).build().unwrap_err();
+    let message: String = err.to_string();
+    assert_eq!(message, "pin number out of range");
+}
@@ -0,0 +1,57 @@
+// Option fields are recognised by their full std/core path as well as by the
+// bare `Option` name, while a type that only happens to be called `Option` in
+// another module is treated as a regular value. #[builder(optional)] marks a
+// field whose Option type is hidden behind an alias, and #[builder(required)]
+// makes an Option field mandatory.
+
+use derive_builder::Builder;
+use std::collections::BTreeSet;
+
+mod settings {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Option<T>(pub T);
+}
+
+type MaybePort = Option<u16>;
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    host: std::option::Option<String>,
+    backlog: core::option::Option<u32>,
+    mode: settings::Option<u8>,
+    #[builder(optional)]
+    port: MaybePort,
+    #[builder(required)]
+    proxy: Option<String>,
+    #[builder(each = "tag")]
+    tags: std::collections::BTreeSet<&'static str>,
+    #[builder(each = "alias")]
+    aliases: ::std::vec::Vec<String>,
+}
+
+fn main() {
+    let server = Server::builder()
+        .mode(settings::Option(1))
+        .port(8080)
+        .proxy(None)
+        .tag("a")
+        .alias("www".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.host, None);
+    assert_eq!(server.backlog, None);
+    assert_eq!(server.mode, settings::Option(1));
+    assert_eq!(server.port, Some(8080));
+    assert_eq!(server.proxy, None);
+    assert_eq!(server.tags, BTreeSet::from(["a"]));
+    assert_eq!(server.aliases, ["www"]);
+
+    let mut builder = Server::builder();
+    builder.host("localhost".to_owned()).backlog(16);
+    assert_eq!(builder.get_host(), Some(&"localhost".to_owned()));
+    assert_eq!(builder.get_port(), None);
+    assert!(!builder.is_proxy_set());
+
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "`mode`, `proxy` must be initialized");
+}
@@ -0,0 +1,22 @@
+// Conflicting or misspelled `optional` / `required` keys are reported with a
+// diagnostic that names the intended key.
+
+use derive_builder::Builder;
+
+type MaybePort = Option<u16>;
+
+#[derive(Builder)]
+pub struct Server {
+    #[builder(optinal)]
+    port: MaybePort,
+    #[builder(require)]
+    proxy: Option<String>,
+    #[builder(optional, required)]
+    host: Option<String>,
+    #[builder(optional = true)]
+    backlog: Option<u32>,
+    #[builder(required, each = "tag")]
+    tags: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+// #[deprecated] on a field is copied onto its setter, so calling the setter
+// triggers the deprecation lint.
+
+#![deny(deprecated)]
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Server {
+    address: String,
+    #[deprecated(note = "use `headers` instead")]
+    #[builder(default)]
+    legacy_header: Option<String>,
+}
+
+fn main() {
+    let _ = Server::builder()
+        .address("localhost".to_owned())
+        .legacy_header("x-trace".to_owned())
+        .build();
+}
@@ -0,0 +1,20 @@
+// A setter that collides with another method generated on the builder, such
+// as `merge` or a `get_` accessor, is reported on the field instead of as a
+// duplicate definition.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct GitConfig {
+    merge: bool,
+    name: String,
+    get_name: bool,
+}
+
+#[derive(Builder)]
+pub struct Renamed {
+    #[builder(setter(name = "merge_strategy"))]
+    merge: bool,
+}
+
+fn main() {}
@@ -0,0 +1,52 @@
+// #[builder(each(name = "...", item = "..."))] names the item type explicitly,
+// so `each` works with collections that implement Default and Extend but not
+// IntoIterator. `key` and `value` select the map-style setter for collections
+// that the macro does not recognise as maps. Such fields merge by replacing
+// the whole collection whenever the other builder's differs from the default.
+
+use derive_builder::Builder;
+use std::collections::BTreeMap;
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Registry(BTreeMap<String, u32>);
+
+impl Extend<(String, u32)> for Registry {
+    fn extend<I: IntoIterator<Item = (String, u32)>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+#[derive(Builder, Debug)]
+pub struct Document {
+    #[builder(each(name = "ch", item = "char"), merge = "replace")]
+    text: String,
+    #[builder(each(name = "entry", key = "String", value = "u32"), merge = "replace")]
+    registry: Registry,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+}
+
+fn main() {
+    let document = Document::builder()
+        .ch('h')
+        .ch('i')
+        .extend_text("!?".chars())
+        .entry("a".to_owned(), 1)
+        .extend_registry(vec![("b".to_owned(), 2)])
+        .tag("greeting".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(document.text, "hi!?");
+    assert_eq!(document.registry.0.len(), 2);
+    assert_eq!(document.registry.0["a"], 1);
+    assert_eq!(document.tags, ["greeting"]);
+
+    let mut base = Document::builder();
+    base.ch('a').entry("a".to_owned(), 1).tag("base".to_owned());
+    let mut text = Document::builder();
+    text.ch('b').tag("text".to_owned());
+    let document = base.merge(text).build().unwrap();
+    assert_eq!(document.text, "b");
+    assert_eq!(document.registry.0.len(), 1);
+    assert_eq!(document.tags, ["base", "text"]);
+}
@@ -0,0 +1,21 @@
+// A sub_builder is built through the enclosing builder's `build` receiver, so
+// a builder with the owned pattern cannot be nested in one that only borrows
+// itself. The derive reports this on the field instead of failing inside the
+// generated build method.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+// The typestate diagnostic for a missing field names the setter and build
+// method the builder actually has when they are renamed.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate, setter(prefix = "with"), build_fn(name = "finish"))]
+pub struct Command {
+    executable: String,
+    #[builder(setter(name = "cwd"))]
+    current_dir: String,
+}
+
+fn main() {
+    let _ = Command::builder().with_executable("cargo".to_owned()).finish();
+}
@@ -0,0 +1,13 @@
+// Appending in `merge` iterates the other builder's collection, which a
+// collection with an explicit item type may not support. Such fields have to
+// opt into `merge = "replace"` instead of losing `merge` silently.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Document {
+    #[builder(each(name = "ch", item = "char"))]
+    text: String,
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+// Typestate setters consume the builder to change its type, so a typestate
+// builder is always owned. Asking for another pattern is an error rather than
+// being ignored.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate, pattern = "mutable")]
+pub struct Request {
+    url: String,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable", typestate)]
+pub struct Response {
+    status: u16,
+}
+
+#[derive(Builder)]
+#[builder(typestate, pattern = "owned")]
+pub struct Header {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,39 @@
+// A #[builder] function calls itself once per combination of the `cfg`
+// predicates on its parameters. Parameters with the same predicate share a
+// combination, and more than 8 distinct predicates are rejected instead of
+// generating hundreds of calls.
+
+#![allow(unused_variables)]
+
+use derive_builder::builder;
+
+#[builder]
+fn shared(
+    #[cfg(unix)] a: u8,
+    #[cfg(unix)] b: u8,
+    #[cfg(any())] c: u8,
+    #[cfg(any())] d: u8,
+    #[cfg(not(any()))] e: u8,
+    #[cfg(not(any()))] f: u8,
+    #[cfg(all())] g: u8,
+    #[cfg(all())] h: u8,
+    #[cfg(not(all()))] i: u8,
+    #[cfg(test)] j: u8,
+) {
+}
+
+#[builder]
+fn distinct(
+    #[cfg(any())] a: u8,
+    #[cfg(not(any()))] b: u8,
+    #[cfg(all())] c: u8,
+    #[cfg(not(all()))] d: u8,
+    #[cfg(any(any()))] e: u8,
+    #[cfg(all(all()))] f: u8,
+    #[cfg(unix)] g: u8,
+    #[cfg(windows)] h: u8,
+    #[cfg(test)] i: u8,
+) {
+}
+
+fn main() {}
@@ -10,4 +10,45 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-default-field.rs");
+    t.pass("tests/11-uninitialized-fields.rs");
+    t.pass("tests/12-generics.rs");
+    t.pass("tests/13-builder-pattern.rs");
+    t.compile_fail("tests/14-unrecognized-pattern.rs");
+    t.pass("tests/15-typestate.rs");
+    t.compile_fail("tests/16-typestate-missing-field.rs");
+    t.pass("tests/17-setter-options.rs");
+    t.pass("tests/18-try-setter.rs");
+    t.pass("tests/19-build-fn.rs");
+    t.pass("tests/20-tuple-struct.rs");
+    t.pass("tests/21-enum.rs");
+    t.compile_fail("tests/22-union.rs");
+    t.pass("tests/23-each-collections.rs");
+    t.pass("tests/24-combined-attributes.rs");
+    t.compile_fail("tests/25-attribute-errors.rs");
+    t.pass("tests/26-builder-customisation.rs");
+    t.pass("tests/27-to-builder.rs");
+    t.pass("tests/28-merge.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/29-serde.rs");
+    t.pass("tests/30-env-fallback.rs");
+    t.pass("tests/31-sub-builder.rs");
+    t.pass("tests/32-skip-and-struct-default.rs");
+    t.pass("tests/33-introspection.rs");
+    t.pass("tests/34-function-builder.rs");
+    t.pass("tests/35-const-builder.rs");
+    t.compile_fail("tests/36-const-missing-field.rs");
+    t.pass("tests/37-forwarded-attributes.rs");
+    t.pass("tests/38-setter-naming.rs");
+    t.pass("tests/39-no-std.rs");
+    t.pass("tests/40-option-detection.rs");
+    t.compile_fail("tests/41-option-override-errors.rs");
+    t.compile_fail("tests/42-deprecated-setter.rs");
+    t.compile_fail("tests/43-method-name-clash.rs");
+    t.pass("tests/44-each-item-type.rs");
+    t.compile_fail("tests/45-sub-builder-pattern.rs");
+    t.compile_fail("tests/46-typestate-renamed-methods.rs");
+    t.compile_fail("tests/47-each-item-merge.rs");
+    t.compile_fail("tests/48-typestate-pattern.rs");
+    t.compile_fail("tests/49-function-cfg-limit.rs");
 }